@@ -8,4 +8,5 @@ cargo fmt --all -- --check
 cargo clippy --quiet --workspace --all-targets --all-features --  -D warnings -W clippy::all
 cargo test --quiet --workspace --all-targets --all-features
 cargo test --quiet --workspace --doc
+cargo test -p sof-chargen-js --target wasm32-unknown-unknown # the wasm test runner rejects --quiet
 trunk build
@@ -0,0 +1,346 @@
+// Generates characters in bulk, for when a session needs a few dozen NPCs and clicking through
+// the GUI for each one won't do. Every choice is answered by a `sim::Policy`, and the whole batch
+// comes from one seed, so the same command line always gives the same characters.
+//
+// `simulate` runs thousands of characters the same way and reports on them as a whole instead,
+// eg. to see how a change to the tables or a policy moves the odds of each career.
+use clap::{Parser, Subcommand, ValueEnum};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use sof_chargen::sim::{self, Answer, PickRoll, Policy, Report, Select};
+use sof_chargen::{BirthOmen, Character};
+use std::error::Error;
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum Format {
+    Ron,
+    Json,
+    /// One row per character, for spreadsheets
+    Csv,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum ReportFormat {
+    Json,
+    Ron,
+}
+
+/// How every choice is answered
+#[derive(Debug, clap::Args)]
+struct PolicyArgs {
+    /// How to pick from lists: random, first or last
+    #[arg(long, default_value = "random")]
+    select: Select,
+    /// How to answer yes or no questions: random, always or never
+    #[arg(long, default_value = "random")]
+    answer: Answer,
+    /// What to do when prophetic signs let you pick a roll: never, highest or lowest
+    #[arg(long, default_value = "never")]
+    pick_roll: PickRoll,
+    /// Always take this birth omen, eg. "Prophetic Signs" or just "prophetic"
+    #[arg(long)]
+    omen: Option<BirthOmen>,
+}
+
+impl PolicyArgs {
+    fn policy(&self) -> Policy {
+        Policy {
+            select: self.select,
+            answer: self.answer,
+            pick_roll: self.pick_roll,
+            omen: self.omen,
+        }
+    }
+}
+
+/// Generates characters without the GUI, answering every choice by a policy
+#[derive(Debug, Parser)]
+#[command(version, args_conflicts_with_subcommands = true)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// How many characters to generate
+    #[arg(short = 'n', long, default_value_t = 1)]
+    count: usize,
+    /// Repeats an earlier batch. Picked at random (and printed) if not given.
+    #[arg(long)]
+    seed: Option<u64>,
+    #[arg(short, long, value_enum, default_value_t = Format::Ron)]
+    format: Format,
+    #[command(flatten)]
+    policy: PolicyArgs,
+    /// Characters are named this followed by their number
+    #[arg(long, default_value = "NPC")]
+    name: String,
+    /// Where to write the characters, rather than stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Generates many characters and reports on them as a whole, eg. how often each career came up
+    Simulate(SimulateArgs),
+}
+
+#[derive(Debug, clap::Args)]
+struct SimulateArgs {
+    /// How many characters to generate
+    #[arg(short = 'n', long, default_value_t = 10000)]
+    count: usize,
+    /// Repeats an earlier simulation. Picked at random (and printed) if not given.
+    #[arg(long)]
+    seed: Option<u64>,
+    #[arg(short, long, value_enum, default_value_t = ReportFormat::Json)]
+    format: ReportFormat,
+    #[command(flatten)]
+    policy: PolicyArgs,
+    /// Where to write the report, rather than stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// Generates the batch, skipping (and warning about) any character that doesn't finish chargen
+fn generate(args: &Args, seed: u64) -> Vec<Character> {
+    let policy = args.policy.policy();
+    let mut rng = StdRng::seed_from_u64(seed);
+    (1..=args.count)
+        .filter_map(|i| {
+            let (mut character, _, failure) = sim::generate(&policy, &mut rng);
+            if let Some(failure) = failure {
+                eprintln!("skipping character {i}: {failure}");
+                return None;
+            }
+            character.name = format!("{} {i}", args.name);
+            Some(character)
+        })
+        .collect()
+}
+
+fn join(things: impl IntoIterator<Item = impl ToString>) -> String {
+    things
+        .into_iter()
+        .map(|thing| thing.to_string())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn or_blank(thing: Option<impl ToString>) -> String {
+    thing.map(|thing| thing.to_string()).unwrap_or_default()
+}
+
+fn write_csv(characters: &[Character], out: impl Write) -> Result<(), Box<dyn Error>> {
+    let mut writer = csv::Writer::from_writer(out);
+    let mut header = vec![String::from("Name")];
+    header.extend(
+        Character::default()
+            .stats
+            .iter()
+            .map(|(stat, _)| stat.to_string()),
+    );
+    header.extend(
+        [
+            "Omen",
+            "Born",
+            "Demographic",
+            "Culture",
+            "Faith",
+            "Affiliation",
+            "Rank",
+            "Careers",
+            "Traits",
+        ]
+        .map(String::from),
+    );
+    writer.write_record(&header)?;
+
+    for character in characters {
+        let location = character.birth_location.as_ref();
+        let mut record = vec![character.name.clone()];
+        record.extend(character.stats.values().map(|value| or_blank(*value)));
+        record.extend([
+            or_blank(character.omen),
+            or_blank(location.map(|location| &location.name)),
+            or_blank(location.map(|location| format!("{:?}", location.demographic))),
+            or_blank(character.culture),
+            or_blank(character.faith),
+            or_blank(character.affiliation),
+            or_blank(character.rank),
+            join(character.careers.iter().map(|career| career.name())),
+            join(&character.traits),
+        ]);
+        writer.write_record(&record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write(
+    characters: &[Character],
+    format: Format,
+    mut out: impl Write,
+) -> Result<(), Box<dyn Error>> {
+    match format {
+        Format::Ron => {
+            let pretty = ron::ser::PrettyConfig::default();
+            writeln!(out, "{}", ron::ser::to_string_pretty(characters, pretty)?)?;
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut out, characters)?;
+            writeln!(out)?;
+        }
+        Format::Csv => write_csv(characters, out)?,
+    }
+    Ok(())
+}
+
+fn simulate(args: &SimulateArgs, seed: u64) -> Report {
+    let mut rng = StdRng::seed_from_u64(seed);
+    sim::simulate(args.count, &args.policy.policy(), &mut rng)
+}
+
+fn write_report(
+    report: &Report,
+    format: ReportFormat,
+    mut out: impl Write,
+) -> Result<(), Box<dyn Error>> {
+    match format {
+        ReportFormat::Json => serde_json::to_writer_pretty(&mut out, report)?,
+        ReportFormat::Ron => {
+            let pretty = ron::ser::PrettyConfig::default();
+            write!(out, "{}", ron::ser::to_string_pretty(report, pretty)?)?;
+        }
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+fn seed(seed: Option<u64>) -> u64 {
+    seed.unwrap_or_else(|| {
+        let seed = rand::rng().random();
+        eprintln!("seed: {seed}");
+        seed
+    })
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    if let Some(Command::Simulate(args)) = &args.command {
+        let report = simulate(args, seed(args.seed));
+        return match &args.output {
+            Some(path) => write_report(&report, args.format, File::create(path)?),
+            None => write_report(&report, args.format, std::io::stdout().lock()),
+        };
+    }
+
+    let characters = generate(&args, seed(args.seed));
+    match &args.output {
+        Some(path) => write(&characters, args.format, File::create(path)?),
+        None => write(&characters, args.format, std::io::stdout().lock()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(command: &str) -> String {
+        let args = Args::parse_from(command.split(' '));
+        let mut out = vec![];
+        write(&generate(&args, args.seed.unwrap()), args.format, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_seeded() {
+        let ron = run("cli -n 5 --seed 12");
+        assert_eq!(ron, run("cli -n 5 --seed 12"));
+        assert_ne!(ron, run("cli -n 5 --seed 13"));
+        let characters: Vec<Character> = ron::from_str(&ron).unwrap();
+        assert_eq!(characters.len(), 5);
+        assert_eq!(characters[4].name, "NPC 5");
+    }
+
+    #[test]
+    fn test_formats() {
+        let json = run("cli -n 3 --seed 1 -f json --select first --omen Shooting");
+        let characters: Vec<Character> = serde_json::from_str(&json).unwrap();
+        assert!(
+            characters
+                .iter()
+                .all(|character| character.omen.unwrap().to_string() == "Shooting Star")
+        );
+
+        let csv = run("cli -n 3 --seed 1 -f csv --name Guard");
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+        let header = reader.headers().unwrap().clone();
+        assert_eq!(&header[0], "Name");
+        assert_eq!(&header[1], "Arms");
+        let rows: Vec<_> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(&rows[2][0], "Guard 3");
+        assert!(rows.iter().all(|row| row.len() == header.len()));
+    }
+
+    fn run_simulate(command: &str) -> String {
+        let Some(Command::Simulate(args)) = Args::parse_from(command.split(' ')).command else {
+            panic!("{command} isn't a simulation");
+        };
+        let mut out = vec![];
+        write_report(&simulate(&args, args.seed.unwrap()), args.format, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_simulate() {
+        let json = run_simulate("cli simulate -n 40 --seed 4 --pick-roll highest --omen prophetic");
+        assert_eq!(
+            json,
+            run_simulate("cli simulate -n 40 --seed 4 --pick-roll highest --omen prophetic")
+        );
+        let report: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(report["runs"], 40);
+        assert_eq!(report["omens"]["Prophetic Signs"], 40);
+        assert_eq!(
+            report["stats"]["Arms"]["counts"]
+                .as_object()
+                .unwrap()
+                .values()
+                .map(|n| n.as_u64().unwrap())
+                .sum::<u64>(),
+            40
+        );
+
+        let ron = run_simulate("cli simulate -n 5 --seed 4 -f ron");
+        #[derive(serde::Deserialize)]
+        struct Summary {
+            runs: usize,
+        }
+        assert_eq!(ron::from_str::<Summary>(&ron).unwrap().runs, 5);
+
+        // the batch options are for making characters, not simulating them
+        assert!(Args::try_parse_from(["cli", "--name", "Guard", "simulate"]).is_err());
+    }
+
+    #[test]
+    fn test_bad_policy() {
+        assert!(Args::try_parse_from(["cli", "--answer", "sometimes"]).is_err());
+
+        // an omen that's misspelt or could be more than one is turned away, not picked at random
+        for omen in ["Shootng", "p"] {
+            let error = Args::try_parse_from(["cli", "--omen", omen]).unwrap_err();
+            assert_eq!(error.kind(), clap::error::ErrorKind::ValueValidation);
+            assert!(
+                error.to_string().contains(&format!("\"{omen}\"")),
+                "{error}"
+            );
+        }
+        let error = Args::try_parse_from(["cli", "simulate", "--omen", "Shootng"]).unwrap_err();
+        assert_eq!(error.kind(), clap::error::ErrorKind::ValueValidation);
+        let args = Args::try_parse_from(["cli", "--omen", "shooting star"]).unwrap();
+        assert_eq!(args.policy.omen, Some(BirthOmen::ShootingStar));
+    }
+}
@@ -0,0 +1,55 @@
+/*
+ * Runs a whole character through chargen, taking the first option every time, and prints each
+ * choice along the way and then the finished character.
+ *
+ *   cargo build -p sof-chargen-ffi
+ *   cc sof-chargen-ffi/examples/generate.c -I sof-chargen-ffi/include \
+ *       target/debug/libsof_chargen_ffi.a -lpthread -ldl -lm -o generate
+ */
+#include <stdio.h>
+#include <string.h>
+
+#include "sof_chargen.h"
+
+/* a real host would parse the JSON; this only needs to know what kind of choice it is */
+static const char *reply_to(const char *choice) {
+    if (strstr(choice, "\"kind\":\"selection\"")) {
+        return "{\"selection\":0}";
+    }
+    if (strstr(choice, "\"kind\":\"string\"")) {
+        return "{\"string\":\"Example\"}";
+    }
+    if (strstr(choice, "\"kind\":\"pick_roll\"")) {
+        return "{\"pick_roll\":null}";
+    }
+    if (strstr(choice, "\"kind\":\"question\"")) {
+        return "{\"question\":true}";
+    }
+    return "\"dismiss\"";
+}
+
+int main(void) {
+    if (sof_abi_version() != SOF_ABI_VERSION) {
+        fprintf(stderr, "built against a different version of the library\n");
+        return 1;
+    }
+
+    SofSession *session = sof_session_new();
+    char *choice;
+    while ((choice = sof_session_choice(session)) != NULL) {
+        printf("choice: %s\n", choice);
+        SofStatus status = sof_session_answer(session, reply_to(choice));
+        sof_string_free(choice);
+        if (status != SOF_STATUS_OK) {
+            fprintf(stderr, "couldn't answer: %s\n", sof_last_error());
+            sof_session_free(session);
+            return 1;
+        }
+    }
+
+    char *character = sof_session_character(session);
+    printf("character: %s\n", character);
+    sof_string_free(character);
+    sof_session_free(session);
+    return 0;
+}
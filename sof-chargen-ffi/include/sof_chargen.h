@@ -0,0 +1,110 @@
+#ifndef SOF_CHARGEN_H
+#define SOF_CHARGEN_H
+
+/* Generated by cbindgen from sof-chargen-ffi/src/lib.rs - run `UPDATE_HEADER=1 cargo test -p sof-chargen-ffi` to regenerate. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Bumped whenever a function changes in a way that would break a host built against an older
+// header. Compare it with `sof_abi_version()` to check the library matches.
+#define SOF_ABI_VERSION 2
+
+// How a call that can fail went. Anything but OK leaves a message in `sof_last_error`.
+typedef enum SofStatus {
+  SOF_STATUS_OK = 0,
+  // A pointer that mustn't be NULL was
+  SOF_STATUS_NULL_ARGUMENT,
+  // A string wasn't UTF-8
+  SOF_STATUS_BAD_UTF8,
+  // A reply wasn't JSON in the shape of one
+  SOF_STATUS_BAD_JSON,
+  // A reply didn't fit the choice, which is left waiting for a better one
+  SOF_STATUS_BAD_REPLY,
+  // A save wasn't one, or was from a version that can't be read
+  SOF_STATUS_BAD_SAVE,
+  // Something went wrong inside the library. Functions that hand out a pointer give NULL instead.
+  SOF_STATUS_INTERNAL,
+} SofStatus;
+
+// One character's trip through chargen. Only ever handled through a pointer.
+typedef struct SofSession SofSession;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// The `SOF_ABI_VERSION` the library was built with
+uint32_t sof_abi_version(void);
+
+// Starts a new character. Free it with `sof_session_free`.
+struct SofSession *sof_session_new(void);
+
+// Picks up a save, as made by `sof_session_save` or any other front-end, into `*session`.
+// Free it with `sof_session_free`. `*session` is set to NULL if the save can't be read.
+//
+// # Safety
+// `save` must be NULL or a NUL-terminated string, and `session` NULL or somewhere to write to.
+enum SofStatus sof_session_load(const char *save, struct SofSession **session);
+
+// Frees a session. Does nothing if given NULL.
+//
+// # Safety
+// `session` must be NULL or from `sof_session_new` or `sof_session_load`, and not already freed.
+void sof_session_free(struct SofSession *session);
+
+// The choice being asked as JSON, eg. `{"kind":"question","description":"Convert?"}`,
+// or NULL once chargen is over.
+//
+// # Safety
+// `session` must be NULL or a live session.
+char *sof_session_choice(const struct SofSession *session);
+
+// Answers the current choice with a JSON reply, eg. `{"selection":0}` or `"dismiss"`,
+// and moves on to the next one.
+//
+// # Safety
+// `session` must be NULL or a live session, and `reply` NULL or a NUL-terminated string.
+enum SofStatus sof_session_answer(struct SofSession *session, const char *reply);
+
+// Whether chargen is over, and the character's finished
+//
+// # Safety
+// `session` must be NULL or a live session.
+bool sof_session_finished(const struct SofSession *session);
+
+// The character as JSON, as far as it's got
+//
+// # Safety
+// `session` must be NULL or a live session.
+char *sof_session_character(const struct SofSession *session);
+
+// Everything that's happened so far, a line at a time
+//
+// # Safety
+// `session` must be NULL or a live session.
+char *sof_session_log(const struct SofSession *session);
+
+// The save, in the same format as `character.sof`
+//
+// # Safety
+// `session` must be NULL or a live session.
+char *sof_session_save(const struct SofSession *session);
+
+// Why the last call on this thread that failed did, or NULL if none have.
+// The library keeps hold of it, and it lasts until the next call that fails.
+const char *sof_last_error(void);
+
+// Gives back a string handed out by the library. Does nothing if given NULL.
+//
+// # Safety
+// `s` must be NULL or a string from this library that hasn't already been freed.
+void sof_string_free(char *s);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SOF_CHARGEN_H */
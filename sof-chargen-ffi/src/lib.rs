@@ -0,0 +1,444 @@
+// A C ABI for hosts that can't link Rust directly, eg. a C++ VTT plugin. Everything crosses as
+// NUL-terminated UTF-8: choices, replies and characters as JSON, in the same shapes the HTTP server
+// uses, and saves as the same RON as `character.sof`. Strings handed out belong to the caller, who
+// gives them back with `sof_string_free`.
+//
+// The header in include/ is generated from this file by cbindgen; a test checks it's up to date.
+use sof_chargen::ipc::Reply;
+use sof_chargen::session::RemoteSession;
+use sof_chargen::{Backend, Character};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::panic::AssertUnwindSafe;
+use std::ptr;
+
+/// Bumped whenever a function changes in a way that would break a host built against an older
+/// header. Compare it with `sof_abi_version()` to check the library matches.
+pub const SOF_ABI_VERSION: u32 = 2;
+
+/// One character's trip through chargen. Only ever handled through a pointer.
+pub struct SofSession(RemoteSession);
+
+/// How a call that can fail went. Anything but OK leaves a message in `sof_last_error`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SofStatus {
+    Ok = 0,
+    /// A pointer that mustn't be NULL was
+    NullArgument,
+    /// A string wasn't UTF-8
+    BadUtf8,
+    /// A reply wasn't JSON in the shape of one
+    BadJson,
+    /// A reply didn't fit the choice, which is left waiting for a better one
+    BadReply,
+    /// A save wasn't one, or was from a version that can't be read
+    BadSave,
+    /// Something went wrong inside the library. Functions that hand out a pointer give NULL instead.
+    Internal,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+// JSON and RON escape anything that'd be a NUL, but drop them rather than trust that
+fn c_string(s: String) -> CString {
+    CString::new(s).unwrap_or_else(|e| {
+        let mut bytes = e.into_vec();
+        bytes.retain(|&b| b != 0);
+        CString::new(bytes).unwrap()
+    })
+}
+
+fn fail(status: SofStatus, message: impl ToString) -> SofStatus {
+    LAST_ERROR.set(Some(c_string(message.to_string())));
+    status
+}
+
+fn hand_out(s: String) -> *mut c_char {
+    c_string(s).into_raw()
+}
+
+// a panic mustn't unwind into the host, which would abort it, so it's caught and reported instead
+fn guard<T>(default: T, f: impl FnOnce() -> T) -> T {
+    std::panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|panic| {
+        let message = match panic.downcast_ref::<&str>() {
+            Some(message) => message.to_string(),
+            None => panic.downcast_ref::<String>().cloned().unwrap_or_default(),
+        };
+        fail(SofStatus::Internal, format!("internal error: {message}"));
+        default
+    })
+}
+
+unsafe fn read<'a>(s: *const c_char, what: &str) -> Result<&'a str, SofStatus> {
+    if s.is_null() {
+        return Err(fail(SofStatus::NullArgument, format!("{what} was NULL")));
+    }
+    unsafe { CStr::from_ptr(s) }
+        .to_str()
+        .map_err(|e| fail(SofStatus::BadUtf8, format!("{what} wasn't UTF-8: {e}")))
+}
+
+unsafe fn with_session<T>(
+    session: *const SofSession,
+    default: T,
+    f: impl FnOnce(&RemoteSession) -> T,
+) -> T {
+    match unsafe { session.as_ref() } {
+        Some(session) => guard(default, || f(&session.0)),
+        None => {
+            fail(SofStatus::NullArgument, "session was NULL");
+            default
+        }
+    }
+}
+
+/// The `SOF_ABI_VERSION` the library was built with
+#[unsafe(no_mangle)]
+pub extern "C" fn sof_abi_version() -> u32 {
+    SOF_ABI_VERSION
+}
+
+/// Starts a new character. Free it with `sof_session_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn sof_session_new() -> *mut SofSession {
+    guard(ptr::null_mut(), || {
+        Box::into_raw(Box::new(SofSession(RemoteSession::default())))
+    })
+}
+
+/// Picks up a save, as made by `sof_session_save` or any other front-end, into `*session`.
+/// Free it with `sof_session_free`. `*session` is set to NULL if the save can't be read.
+///
+/// # Safety
+/// `save` must be NULL or a NUL-terminated string, and `session` NULL or somewhere to write to.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sof_session_load(
+    save: *const c_char,
+    session: *mut *mut SofSession,
+) -> SofStatus {
+    let Some(session) = (unsafe { session.as_mut() }) else {
+        return fail(SofStatus::NullArgument, "session was NULL");
+    };
+    *session = ptr::null_mut();
+    let save = match unsafe { read(save, "save") } {
+        Ok(save) => save,
+        Err(status) => return status,
+    };
+    guard(SofStatus::Internal, || match RemoteSession::load(save) {
+        Ok(loaded) => {
+            *session = Box::into_raw(Box::new(SofSession(loaded)));
+            SofStatus::Ok
+        }
+        Err(e) => fail(SofStatus::BadSave, format!("bad save: {e}")),
+    })
+}
+
+/// Frees a session. Does nothing if given NULL.
+///
+/// # Safety
+/// `session` must be NULL or from `sof_session_new` or `sof_session_load`, and not already freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sof_session_free(session: *mut SofSession) {
+    if !session.is_null() {
+        guard((), || drop(unsafe { Box::from_raw(session) }));
+    }
+}
+
+/// The choice being asked as JSON, eg. `{"kind":"question","description":"Convert?"}`,
+/// or NULL once chargen is over.
+///
+/// # Safety
+/// `session` must be NULL or a live session.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sof_session_choice(session: *const SofSession) -> *mut c_char {
+    unsafe {
+        with_session(session, ptr::null_mut(), |session| {
+            session.choice().map_or(ptr::null_mut(), |choice| {
+                hand_out(serde_json::to_string(&choice).unwrap())
+            })
+        })
+    }
+}
+
+/// Answers the current choice with a JSON reply, eg. `{"selection":0}` or `"dismiss"`,
+/// and moves on to the next one.
+///
+/// # Safety
+/// `session` must be NULL or a live session, and `reply` NULL or a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sof_session_answer(
+    session: *mut SofSession,
+    reply: *const c_char,
+) -> SofStatus {
+    let Some(session) = (unsafe { session.as_mut() }) else {
+        return fail(SofStatus::NullArgument, "session was NULL");
+    };
+    let reply = match unsafe { read(reply, "reply") } {
+        Ok(reply) => reply,
+        Err(status) => return status,
+    };
+    let reply: Reply = match serde_json::from_str(reply) {
+        Ok(reply) => reply,
+        Err(e) => return fail(SofStatus::BadJson, format!("bad reply: {e}")),
+    };
+    guard(SofStatus::Internal, || match session.0.reply(reply) {
+        Ok(()) => SofStatus::Ok,
+        Err(e) => fail(SofStatus::BadReply, e),
+    })
+}
+
+/// Whether chargen is over, and the character's finished
+///
+/// # Safety
+/// `session` must be NULL or a live session.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sof_session_finished(session: *const SofSession) -> bool {
+    unsafe { with_session(session, false, RemoteSession::is_finished) }
+}
+
+/// The character as JSON, as far as it's got
+///
+/// # Safety
+/// `session` must be NULL or a live session.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sof_session_character(session: *const SofSession) -> *mut c_char {
+    unsafe {
+        with_session(session, ptr::null_mut(), |session| {
+            let character: &Character = &session.backend().get_character();
+            hand_out(serde_json::to_string(character).unwrap())
+        })
+    }
+}
+
+/// Everything that's happened so far, a line at a time
+///
+/// # Safety
+/// `session` must be NULL or a live session.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sof_session_log(session: *const SofSession) -> *mut c_char {
+    unsafe {
+        with_session(session, ptr::null_mut(), |session| {
+            let log = session.backend().log.read().unwrap();
+            hand_out(log.trim_start().to_owned())
+        })
+    }
+}
+
+/// The save, in the same format as `character.sof`
+///
+/// # Safety
+/// `session` must be NULL or a live session.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sof_session_save(session: *const SofSession) -> *mut c_char {
+    unsafe { with_session(session, ptr::null_mut(), |session| hand_out(session.save())) }
+}
+
+/// Why the last call on this thread that failed did, or NULL if none have.
+/// The library keeps hold of it, and it lasts until the next call that fails.
+#[unsafe(no_mangle)]
+pub extern "C" fn sof_last_error() -> *const c_char {
+    LAST_ERROR.with_borrow(|error| error.as_ref().map_or(ptr::null(), |error| error.as_ptr()))
+}
+
+/// Gives back a string handed out by the library. Does nothing if given NULL.
+///
+/// # Safety
+/// `s` must be NULL or a string from this library that hasn't already been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sof_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    // takes a string handed out by the library
+    fn take(s: *mut c_char) -> Option<String> {
+        (!s.is_null()).then(|| unsafe {
+            let owned = CStr::from_ptr(s).to_str().unwrap().to_owned();
+            sof_string_free(s);
+            owned
+        })
+    }
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(sof_last_error()) }
+            .to_str()
+            .unwrap()
+            .to_owned()
+    }
+
+    fn answer(session: *mut SofSession, reply: &str) -> SofStatus {
+        let reply = CString::new(reply).unwrap();
+        unsafe { sof_session_answer(session, reply.as_ptr()) }
+    }
+
+    #[test]
+    fn test_session() {
+        let session = sof_session_new();
+        assert_eq!(answer(session, r#"{"question":true}"#), SofStatus::BadReply);
+        assert!(last_error().contains("doesn't answer a selection"));
+        assert_eq!(answer(session, "nonsense"), SofStatus::BadJson);
+        assert_eq!(
+            unsafe { sof_session_answer(session, ptr::null()) },
+            SofStatus::NullArgument
+        );
+
+        while let Some(choice) = take(unsafe { sof_session_choice(session) }) {
+            let reply = match &choice {
+                c if c.contains(r#""kind":"selection""#) => r#"{"selection":0}"#,
+                c if c.contains(r#""kind":"string""#) => r#"{"string":"example"}"#,
+                c if c.contains(r#""kind":"pick_roll""#) => r#"{"pick_roll":null}"#,
+                c if c.contains(r#""kind":"question""#) => r#"{"question":true}"#,
+                _ => panic!("{choice}"),
+            };
+            assert_eq!(answer(session, reply), SofStatus::Ok, "{}", last_error());
+        }
+        assert!(unsafe { sof_session_finished(session) });
+
+        let character = take(unsafe { sof_session_character(session) }).unwrap();
+        let character: Character = serde_json::from_str(&character).unwrap();
+        assert_eq!(character.careers.len(), 1);
+        let log = take(unsafe { sof_session_log(session) }).unwrap();
+        assert!(log.contains("You were born in"));
+
+        let save = CString::new(take(unsafe { sof_session_save(session) }).unwrap()).unwrap();
+        let mut loaded = ptr::null_mut();
+        let status = unsafe { sof_session_load(save.as_ptr(), &mut loaded) };
+        assert_eq!(status, SofStatus::Ok);
+        assert!(unsafe { sof_session_finished(loaded) });
+
+        // a save that isn't one is told apart from a reply that isn't JSON
+        let bad = CString::new("nonsense").unwrap();
+        let mut unloaded = session;
+        let status = unsafe { sof_session_load(bad.as_ptr(), &mut unloaded) };
+        assert_eq!(status, SofStatus::BadSave);
+        assert!(unloaded.is_null());
+        assert!(last_error().starts_with("bad save"));
+        assert_eq!(
+            unsafe { sof_session_load(bad.as_ptr(), ptr::null_mut()) },
+            SofStatus::NullArgument
+        );
+
+        unsafe {
+            sof_session_free(session);
+            sof_session_free(loaded);
+            sof_session_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_panic() {
+        // a lock poisoned by a panic elsewhere panics again on every use
+        let session = sof_session_new();
+        let backend = unsafe { &*session }.0.backend();
+        std::thread::scope(|scope| {
+            let poisoner = scope.spawn(|| {
+                let _character = backend.character.write().unwrap();
+                panic!("poisoned");
+            });
+            assert!(poisoner.join().is_err());
+        });
+
+        assert!(unsafe { sof_session_character(session) }.is_null());
+        assert!(
+            last_error().starts_with("internal error"),
+            "{}",
+            last_error()
+        );
+        let reply = r#"{"selection":0}"#;
+        assert_eq!(answer(session, reply), SofStatus::Internal);
+        guard((), || panic!("boom"));
+        assert_eq!(last_error(), "internal error: boom");
+        unsafe { sof_session_free(session) };
+    }
+
+    fn crate_dir() -> &'static Path {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+    }
+
+    #[test]
+    fn test_header() {
+        let config = cbindgen::Config::from_file(crate_dir().join("cbindgen.toml")).unwrap();
+        let mut header = vec![];
+        cbindgen::generate_with_config(crate_dir(), config)
+            .unwrap()
+            .write(&mut header);
+        let header = String::from_utf8(header).unwrap();
+
+        let path = crate_dir().join("include/sof_chargen.h");
+        if std::env::var_os("UPDATE_HEADER").is_some() {
+            std::fs::write(&path, &header).unwrap();
+        }
+        assert_eq!(
+            std::fs::read_to_string(path).unwrap_or_default(),
+            header,
+            "the header is out of date; run with UPDATE_HEADER=1 to regenerate it"
+        );
+    }
+
+    // builds the static library, then the example against it, and runs the example
+    #[cfg(unix)]
+    #[test]
+    fn test_c_example() {
+        // cargo says where it put the library, wherever the target directory is
+        let output = Command::new(env!("CARGO"))
+            .args([
+                "build",
+                "--quiet",
+                "--message-format=json",
+                "-p",
+                "sof-chargen-ffi",
+            ])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let library: PathBuf = String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+            .filter(|message| message["reason"] == "compiler-artifact")
+            .flat_map(|message| message["filenames"].as_array().cloned().unwrap_or_default())
+            .filter_map(|file| file.as_str().map(PathBuf::from))
+            .find(|file| file.extension().is_some_and(|ext| ext == "a"))
+            .unwrap();
+        let example = library.with_file_name("sof-chargen-example");
+        let output = Command::new("cc")
+            .arg(crate_dir().join("examples/generate.c"))
+            .arg("-I")
+            .arg(crate_dir().join("include"))
+            .arg(&library)
+            .args(["-lpthread", "-ldl", "-lm", "-o"])
+            .arg(&example)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let output = Command::new(&example).output().unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(
+            output.status.success(),
+            "{stdout}{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let character = stdout
+            .lines()
+            .find_map(|line| line.strip_prefix("character: "))
+            .unwrap();
+        let character: Character = serde_json::from_str(character).unwrap();
+        assert!(character.omen.is_some());
+        assert_eq!(character.careers.len(), 1);
+    }
+}
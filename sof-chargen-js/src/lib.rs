@@ -0,0 +1,246 @@
+// The rules engine for JavaScript, so web front-ends other than the iced one can run real chargen
+// sessions. Choices and answers cross over as plain objects, in the same shape the HTTP server
+// uses, and saves are the same RON as `character.sof`.
+mod snapshot;
+
+pub use snapshot::Snapshot;
+
+use serde::Serialize;
+use sof_chargen::Backend;
+use sof_chargen::ipc::Reply;
+use sof_chargen::session::RemoteSession;
+use wasm_bindgen::prelude::*;
+
+/// The TypeScript types of the plain objects that cross over, as added to the generated `.d.ts`
+pub const TYPES: &str = r#"
+export type Choice =
+    | { kind: "selection"; description: string; options: string[] }
+    | { kind: "string"; description: string }
+    | { kind: "pick_roll"; description: string; roll: string; min: number; max: number }
+    | { kind: "question"; description: string }
+    | { kind: "error"; description: string; error: string };
+
+export type Reply =
+    | { selection: number }
+    | { string: string }
+    | { pick_roll: number | null }
+    | { question: boolean }
+    | "dismiss";
+
+export type Stat =
+    | "Arms" | "Block" | "Swing" | "Thrust"
+    | "Face" | "Charm" | "Impose" | "Read"
+    | "Hands" | "Aim" | "Craft" | "Control"
+    | "Legs" | "Balance" | "Power" | "Dodge"
+    | "Mind" | "Language" | "Lore" | "Observe"
+    | "Magic" | "Luck" | "Stamina" | "Speed";
+
+export interface Career {
+    id: string;
+    name: string;
+    class: string;
+}
+
+export interface Character {
+    name: string;
+    stats: Record<Stat, number | null>;
+    omen: string | null;
+    birthplace: string | null;
+    birthplaceId: string | null;
+    demographic: string | null;
+    culture: string | null;
+    faith: string | null;
+    affiliation: string | null;
+    rank: number | null;
+    careers: Career[];
+    traits: string[];
+    lifeStage: string;
+    guardians: {
+        affiliation: string | null;
+        rank: number | null;
+        career: Career | null;
+        culture: string | null;
+        faith: string | null;
+    };
+}
+"#;
+
+// the declarations are kept apart from the custom section, which wasm-bindgen takes for itself, so
+// the tests can check them against what's really serialized
+#[wasm_bindgen(typescript_custom_section)]
+const TYPESCRIPT: &str = TYPES;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "Choice | undefined")]
+    pub type JsChoice;
+    #[wasm_bindgen(typescript_type = "Reply")]
+    pub type JsReply;
+    #[wasm_bindgen(typescript_type = "Character")]
+    pub type JsCharacter;
+}
+
+// plain objects and nulls, rather than the Maps and undefineds serde-wasm-bindgen defaults to
+fn to_js<T: JsCast>(value: &impl Serialize) -> Result<T, JsError> {
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    Ok(value.serialize(&serializer)?.unchecked_into())
+}
+
+/// One character's trip through chargen
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct Session(RemoteSession);
+
+#[wasm_bindgen]
+impl Session {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Session {
+        Session::default()
+    }
+
+    /// Picks up a save, as made by `save` or any other front-end
+    pub fn load(save: &str) -> Result<Session, JsError> {
+        Ok(Session(RemoteSession::load(save)?))
+    }
+
+    /// What's being asked, or undefined once chargen is over
+    pub fn choice(&self) -> Result<JsChoice, JsError> {
+        match self.0.choice() {
+            Some(choice) => to_js(&choice),
+            None => Ok(JsValue::UNDEFINED.unchecked_into()),
+        }
+    }
+
+    /// Answers the current choice, and returns the next one.
+    /// Throws if the reply doesn't fit the choice, which is left waiting for a better one.
+    pub fn answer(&mut self, reply: JsReply) -> Result<JsChoice, JsError> {
+        let reply: Reply = serde_wasm_bindgen::from_value(reply.into())?;
+        self.0.reply(reply)?;
+        self.choice()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn finished(&self) -> bool {
+        self.0.is_finished()
+    }
+
+    pub fn character(&self) -> Result<JsCharacter, JsError> {
+        to_js(&Snapshot::from(&*self.0.backend().get_character()))
+    }
+
+    /// Everything that's happened so far, a line at a time
+    pub fn log(&self) -> Vec<String> {
+        let log = self.0.backend().log.read().unwrap();
+        log.lines()
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+    pub fn save(&self) -> String {
+        self.0.save()
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use js_sys::{Object, Reflect};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn get(object: &JsValue, key: &str) -> JsValue {
+        Reflect::get(object, &key.into()).unwrap()
+    }
+
+    fn reply(choice: &JsValue) -> JsReply {
+        let reply = Object::new();
+        let (key, value): (&str, JsValue) = match get(choice, "kind").as_string().unwrap().as_str()
+        {
+            "selection" => ("selection", 0.into()),
+            "string" => ("string", "example".into()),
+            "pick_roll" => ("pick_roll", JsValue::NULL),
+            "question" => ("question", true.into()),
+            _ => panic!("{:?}", get(choice, "error")),
+        };
+        Reflect::set(&reply, &key.into(), &value).unwrap();
+        reply.unchecked_into()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_session() {
+        let mut session = Session::new();
+        let mut choice: JsValue = session.choice().unwrap().into();
+        assert_eq!(get(&choice, "kind"), "selection");
+        assert!(
+            session
+                .answer(JsValue::from("dismiss").unchecked_into())
+                .is_err()
+        );
+
+        while !choice.is_undefined() {
+            choice = session.answer(reply(&choice)).unwrap().into();
+        }
+        assert!(session.finished());
+
+        let character: JsValue = session.character().unwrap().into();
+        assert!(get(&get(&character, "stats"), "Arms").as_f64().is_some());
+        assert!(get(&character, "birthplace").is_string());
+        assert!(
+            session
+                .log()
+                .iter()
+                .any(|line| line.starts_with("You were born in"))
+        );
+
+        let loaded = Session::load(&session.save()).unwrap();
+        assert!(loaded.finished());
+        assert_eq!(loaded.save(), session.save());
+        assert!(Session::load("nonsense").is_err());
+    }
+
+    // the names of the fields declared at `indent` in the block opening with `start`
+    fn declared(start: &str, indent: usize) -> Vec<String> {
+        let block = &TYPES[TYPES.find(start).unwrap() + start.len()..];
+        block
+            .lines()
+            .skip(1)
+            .take_while(|line| line.len() - line.trim_start().len() >= indent)
+            .filter(|line| line.len() - line.trim_start().len() == indent)
+            .filter_map(|line| line.trim().split_once(':'))
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+
+    fn keys(object: &JsValue) -> Vec<String> {
+        Object::keys(object.unchecked_ref())
+            .iter()
+            .map(|key| key.as_string().unwrap())
+            .collect()
+    }
+
+    // TYPES is written by hand, so it's checked against what a snapshot really serializes to
+    #[wasm_bindgen_test]
+    fn test_types() {
+        let mut character = sof_chargen::Character::default();
+        let peddler = sof_chargen::data::careers::Career::from_id("peddler").unwrap();
+        character.careers = vec![peddler];
+        character.guardians.career = Some(peddler);
+        let snapshot: JsValue = to_js::<JsValue>(&Snapshot::from(&character)).unwrap();
+
+        assert_eq!(keys(&snapshot), declared("export interface Character {", 4));
+        let guardians = get(&snapshot, "guardians");
+        assert_eq!(keys(&guardians), declared("    guardians: {", 8));
+        let career = get(&get(&snapshot, "careers"), "0");
+        assert_eq!(keys(&career), declared("export interface Career {", 4));
+        assert_eq!(
+            keys(&get(&guardians, "career")),
+            declared("export interface Career {", 4)
+        );
+        for stat in keys(&get(&snapshot, "stats")) {
+            assert!(
+                TYPES.contains(&format!("\"{stat}\"")),
+                "{stat} isn't a Stat"
+            );
+        }
+    }
+}
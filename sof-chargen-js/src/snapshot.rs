@@ -0,0 +1,115 @@
+use enum_map::EnumMap;
+use serde::Serialize;
+use sof_chargen::data::careers::Career;
+use sof_chargen::{Character, Guardians, Stat};
+use std::fmt::Display;
+
+// a save keeps everything by id, for loading back in; a sheet in a browser wants the names
+fn name(thing: Option<impl Display>) -> Option<String> {
+    thing.map(|thing| thing.to_string())
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CareerSnapshot {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub class: String,
+}
+
+impl From<Career> for CareerSnapshot {
+    fn from(career: Career) -> Self {
+        Self {
+            id: career.id(),
+            name: career.name(),
+            class: career.class().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct GuardiansSnapshot {
+    pub affiliation: Option<String>,
+    pub rank: Option<i8>,
+    pub career: Option<CareerSnapshot>,
+    pub culture: Option<String>,
+    pub faith: Option<String>,
+}
+
+impl From<&Guardians> for GuardiansSnapshot {
+    fn from(guardians: &Guardians) -> Self {
+        Self {
+            affiliation: name(guardians.affiliation),
+            rank: guardians.rank,
+            career: guardians.career.map(CareerSnapshot::from),
+            culture: name(guardians.culture),
+            faith: name(guardians.faith),
+        }
+    }
+}
+
+/// A character as it'd be written on a sheet, for JavaScript to show
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    pub name: String,
+    /// In the order they're written on the sheet
+    pub stats: EnumMap<Stat, Option<i8>>,
+    pub omen: Option<String>,
+    pub birthplace: Option<String>,
+    pub birthplace_id: Option<String>,
+    pub demographic: Option<String>,
+    pub culture: Option<String>,
+    pub faith: Option<String>,
+    pub affiliation: Option<String>,
+    pub rank: Option<i8>,
+    pub careers: Vec<CareerSnapshot>,
+    pub traits: Vec<String>,
+    pub life_stage: String,
+    pub guardians: GuardiansSnapshot,
+}
+
+impl From<&Character> for Snapshot {
+    fn from(character: &Character) -> Self {
+        let location = character.birth_location.as_ref();
+        Self {
+            name: character.name.clone(),
+            stats: character.stats,
+            omen: name(character.omen),
+            birthplace: location.map(|location| location.name.clone()),
+            birthplace_id: location.map(|location| location.id.clone()),
+            demographic: location.map(|location| format!("{:?}", location.demographic)),
+            culture: name(character.culture),
+            faith: name(character.faith),
+            affiliation: name(character.affiliation),
+            rank: character.rank,
+            careers: character.careers.iter().copied().map(From::from).collect(),
+            traits: character.traits.clone(),
+            life_stage: format!("{:?}", character.life_stage),
+            guardians: (&character.guardians).into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sof_chargen::data::careers::Affiliation;
+    use sof_chargen::data::locations::Culture;
+
+    #[test]
+    fn test_snapshot() {
+        let mut character = Character::default();
+        character.stats[Stat::Arms] = Some(40);
+        character.culture = Some(Culture::Myrsc(sof_chargen::data::locations::Faith::Grevite));
+        character.affiliation = Some(Affiliation::Watch);
+        character.careers = vec![Career::from_id("peddler").unwrap()];
+
+        let snapshot = Snapshot::from(&character);
+        assert_eq!(snapshot.stats[Stat::Arms], Some(40));
+        assert_eq!(snapshot.culture.as_deref(), Some("Myrsc"));
+        assert_eq!(snapshot.affiliation.as_deref(), Some("Watch"));
+        assert_eq!(snapshot.careers[0].name, "Peddler");
+        assert_eq!(snapshot.life_stage, "Intro");
+        assert_eq!(snapshot.birthplace, None);
+    }
+}
@@ -0,0 +1,279 @@
+// Serves chargen sessions over HTTP on localhost, so web tools and VTT plugins can drive the real
+// rules without linking them. Everything is JSON apart from saves, which are the same RON as
+// `character.sof`.
+//
+//   POST   /sessions               starts a session, or picks one up if sent a save; gives its id
+//   GET    /sessions/{id}          the character, its log, and whether chargen is over
+//   GET    /sessions/{id}/choice   what's being asked, or null once chargen is over
+//   POST   /sessions/{id}/answer   answers it with a reply, eg. {"selection": 0}; gives the next choice
+//   GET    /sessions/{id}/save     the save
+//   GET    /sessions/{id}/foundry  the character as a Foundry VTT actor, from the bundled template
+//   DELETE /sessions/{id}          forgets the session
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Serialize;
+use sof_chargen::foundry::FoundryTemplate;
+use sof_chargen::ipc::{ChoiceView, Reply};
+use sof_chargen::session::RemoteSession;
+use sof_chargen::{Backend, Character, ChargenError};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:3000";
+
+#[derive(Default)]
+struct Sessions {
+    sessions: Mutex<HashMap<u64, RemoteSession>>,
+    next_id: AtomicU64,
+}
+
+impl Sessions {
+    fn with<T>(
+        &self,
+        id: u64,
+        f: impl FnOnce(&mut RemoteSession) -> Result<T, ApiError>,
+    ) -> Result<T, ApiError> {
+        let mut sessions = self.sessions.lock().unwrap();
+        f(sessions.get_mut(&id).ok_or(ApiError::NoSession(id))?)
+    }
+}
+
+type AppState = State<Arc<Sessions>>;
+
+enum ApiError {
+    NoSession(u64),
+    BadSave(String),
+    BadReply(ChargenError),
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, error) = match self {
+            ApiError::NoSession(id) => (StatusCode::NOT_FOUND, format!("no session {id}")),
+            ApiError::BadSave(e) => (StatusCode::BAD_REQUEST, format!("bad save: {e}")),
+            ApiError::BadReply(e) => (StatusCode::BAD_REQUEST, e.to_string()),
+        };
+        (status, Json(ErrorBody { error })).into_response()
+    }
+}
+
+#[derive(Serialize)]
+struct Created {
+    id: u64,
+}
+
+#[derive(Serialize)]
+struct Sheet<'a> {
+    character: &'a Character,
+    log: Vec<&'a str>,
+    finished: bool,
+}
+
+async fn create(
+    State(sessions): AppState,
+    save: String,
+) -> Result<(StatusCode, Json<Created>), ApiError> {
+    let session = if save.trim().is_empty() {
+        RemoteSession::default()
+    } else {
+        RemoteSession::load(&save).map_err(|e| ApiError::BadSave(e.to_string()))?
+    };
+    let id = sessions.next_id.fetch_add(1, Ordering::Relaxed);
+    sessions.sessions.lock().unwrap().insert(id, session);
+    Ok((StatusCode::CREATED, Json(Created { id })))
+}
+
+async fn sheet(State(sessions): AppState, Path(id): Path<u64>) -> Result<Response, ApiError> {
+    sessions.with(id, |session| {
+        let backend = session.backend();
+        let character = backend.get_character();
+        let log = backend.log.read().unwrap();
+        Ok(Json(Sheet {
+            character: &character,
+            log: log.lines().filter(|line| !line.is_empty()).collect(),
+            finished: session.is_finished(),
+        })
+        .into_response())
+    })
+}
+
+async fn choice(
+    State(sessions): AppState,
+    Path(id): Path<u64>,
+) -> Result<Json<Option<ChoiceView>>, ApiError> {
+    sessions.with(id, |session| Ok(Json(session.choice())))
+}
+
+async fn answer(
+    State(sessions): AppState,
+    Path(id): Path<u64>,
+    Json(reply): Json<Reply>,
+) -> Result<Json<Option<ChoiceView>>, ApiError> {
+    sessions.with(id, |session| {
+        session.reply(reply).map_err(ApiError::BadReply)?;
+        Ok(Json(session.choice()))
+    })
+}
+
+async fn save(State(sessions): AppState, Path(id): Path<u64>) -> Result<String, ApiError> {
+    sessions.with(id, |session| Ok(session.save()))
+}
+
+async fn foundry(
+    State(sessions): AppState,
+    Path(id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    sessions.with(id, |session| {
+        let backend = session.backend();
+        let log = backend.log.read().unwrap();
+        Ok(Json(
+            FoundryTemplate::bundled().actor(&backend.get_character(), &log),
+        ))
+    })
+}
+
+async fn remove(State(sessions): AppState, Path(id): Path<u64>) -> Result<StatusCode, ApiError> {
+    match sessions.sessions.lock().unwrap().remove(&id) {
+        Some(_) => Ok(StatusCode::NO_CONTENT),
+        None => Err(ApiError::NoSession(id)),
+    }
+}
+
+fn app() -> Router {
+    Router::new()
+        .route("/sessions", post(create))
+        .route("/sessions/{id}", get(sheet).delete(remove))
+        .route("/sessions/{id}/choice", get(choice))
+        .route("/sessions/{id}/answer", post(answer))
+        .route("/sessions/{id}/save", get(save))
+        .route("/sessions/{id}/foundry", get(foundry))
+        .with_state(Arc::new(Sessions::default()))
+}
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let address = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_ADDRESS.to_owned());
+    let listener = tokio::net::TcpListener::bind(&address).await?;
+    println!(
+        "serving chargen sessions on http://{}",
+        listener.local_addr()?
+    );
+    axum::serve(listener, app()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use serde_json::{Value, json};
+    use tower::ServiceExt;
+
+    async fn call(
+        app: &Router,
+        method: &str,
+        uri: &str,
+        body: Option<Value>,
+    ) -> (StatusCode, String) {
+        let request = Request::builder().method(method).uri(uri);
+        let request = match body {
+            Some(body) => request
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        };
+        let response = app.clone().oneshot(request.unwrap()).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    async fn call_json(app: &Router, method: &str, uri: &str, body: Option<Value>) -> Value {
+        let (status, body) = call(app, method, uri, body).await;
+        assert!(status.is_success(), "{status}: {body}");
+        serde_json::from_str(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_session() {
+        let app = app();
+        let id = call_json(&app, "POST", "/sessions", None).await["id"].clone();
+
+        let mut choice = call_json(&app, "GET", &format!("/sessions/{id}/choice"), None).await;
+        assert_eq!(choice["kind"], "selection");
+        let (status, _) = call(
+            &app,
+            "POST",
+            &format!("/sessions/{id}/answer"),
+            Some(json!({"question": true})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        while !choice.is_null() {
+            let reply = match choice["kind"].as_str().unwrap() {
+                "selection" => json!({"selection": 0}),
+                "string" => json!({"string": "example"}),
+                "pick_roll" => json!({"pick_roll": null}),
+                "question" => json!({"question": true}),
+                _ => panic!("{choice}"),
+            };
+            choice = call_json(&app, "POST", &format!("/sessions/{id}/answer"), Some(reply)).await;
+        }
+
+        let sheet = call_json(&app, "GET", &format!("/sessions/{id}"), None).await;
+        assert_eq!(sheet["finished"], true);
+        assert!(sheet["character"]["stats"]["Arms"].is_number());
+        assert!(
+            sheet["log"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|line| line.as_str().unwrap().starts_with("You were born in"))
+        );
+
+        let actor = call_json(&app, "GET", &format!("/sessions/{id}/foundry"), None).await;
+        assert_eq!(
+            actor["system"]["attributes"]["arms"]["value"],
+            sheet["character"]["stats"]["Arms"]
+        );
+        assert_eq!(actor["items"][0]["type"], "career");
+
+        // a save picks up as a new session, in the same state
+        let (status, save) = call(&app, "GET", &format!("/sessions/{id}/save"), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let request = Request::post("/sessions").body(Body::from(save)).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let loaded = call_json(&app, "GET", "/sessions/1", None).await;
+        assert_eq!(loaded["character"], sheet["character"]);
+
+        let (status, _) = call(&app, "DELETE", &format!("/sessions/{id}"), None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, body) = call(&app, "GET", &format!("/sessions/{id}"), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body, r#"{"error":"no session 0"}"#);
+    }
+
+    #[tokio::test]
+    async fn test_bad_save() {
+        let request = Request::post("/sessions")
+            .body(Body::from("nonsense"))
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,439 @@
+// A terminal front-end for chargen, for anywhere the GUI can't go, eg. over SSH. It runs the same
+// sessions as the GUI and saves the same `character.sof`, so a character can be started in one
+// and finished in the other.
+mod sheet;
+mod sidebar;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::{DefaultTerminal, Frame};
+use sof_chargen::ipc::Choice;
+use sof_chargen::save;
+use sof_chargen::session::Session;
+use sof_chargen::sheet::SheetFormat;
+use sof_chargen::{Backend, Character, LogBackend};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const SAVE_FILE: &str = "character.sof";
+
+struct App {
+    session: Session<'static, Arc<LogBackend>>,
+    current_choice: Option<Choice>,
+    save_path: PathBuf,
+    // off if a save that didn't load couldn't be kept aside, so it isn't saved over
+    saving: bool,
+
+    // which option is highlighted, for selections and questions
+    cursor: usize,
+    // what's been typed so far, for traits and picked rolls
+    input: String,
+    // the last thing that went wrong, eg. a picked roll that's out of range
+    warning: Option<String>,
+    finished: bool,
+    // where the sheet went, once it's been written out
+    sheet_path: Option<PathBuf>,
+    quit: bool,
+}
+
+impl App {
+    fn new(save_path: PathBuf) -> Self {
+        let mut warning = None;
+        let mut saving = true;
+        let loaded = match std::fs::read(&save_path) {
+            Ok(raw) => {
+                let loaded = std::str::from_utf8(&raw)
+                    .map_err(|e| e.to_string())
+                    .and_then(|save| save::from_str(save).map_err(|e| e.to_string()));
+                loaded.map_err(|e| (e, Some(raw)))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(LogBackend::default()),
+            Err(e) => Err((e.to_string(), None)),
+        };
+        let backend = loaded.unwrap_or_else(|(e, raw)| {
+            let read = format!("couldn't read {}: {e}", save_path.display());
+            let kept = match raw {
+                Some(raw) => keep_aside(&save_path, &raw).map_err(|e| e.to_string()),
+                None => Err(String::from("it couldn't be copied")),
+            };
+            warning = Some(match kept {
+                Ok(kept) => format!("{read}. It's been kept as {}", kept.display()),
+                Err(e) => {
+                    saving = false;
+                    format!("{read}. It won't be saved over, since {e}")
+                }
+            });
+            LogBackend::default()
+        });
+        Self {
+            session: Session::new(Arc::new(backend)),
+            current_choice: None,
+            save_path,
+            saving,
+            cursor: 0,
+            input: String::new(),
+            warning,
+            finished: false,
+            sheet_path: None,
+            quit: false,
+        }
+    }
+
+    fn backend(&self) -> &LogBackend {
+        self.session.backend()
+    }
+
+    fn save(&mut self) {
+        if !self.saving {
+            return;
+        }
+        if let Err(e) = std::fs::write(&self.save_path, save::to_string(self.backend())) {
+            self.warning = Some(format!("couldn't save {}: {e}", self.save_path.display()));
+        }
+    }
+
+    // moves on to the event's next choice, saving between events like the GUI does
+    fn next_choice(&mut self) {
+        self.current_choice = None;
+        self.cursor = 0;
+        self.input.clear();
+        self.current_choice = self.session.next_choice();
+        if !self.session.in_event() {
+            self.save();
+        }
+    }
+
+    // saves on the way out, unless it's in the middle of an event that can't be picked up again
+    fn close(&mut self) {
+        if !self.session.in_event() {
+            self.save();
+        }
+    }
+
+    fn advance_stage(&mut self) {
+        if self.session.advance_stage() {
+            self.next_choice();
+        } else {
+            self.finished = true;
+        }
+    }
+
+    fn reset(&mut self) {
+        let backend = self.session.backend();
+        *backend.get_character_mut() = Character::default();
+        backend.log.write().unwrap().clear();
+        self.finished = false;
+        self.sheet_path = None;
+        self.save();
+    }
+
+    // a Markdown sheet next to the save, for printing or pasting into a wiki
+    fn write_sheet(&mut self) {
+        let path = self
+            .save_path
+            .with_extension(SheetFormat::Markdown.extension());
+        match std::fs::write(&path, self.backend().sheet(SheetFormat::Markdown)) {
+            Ok(()) => self.sheet_path = Some(path),
+            Err(e) => self.warning = Some(format!("couldn't write {}: {e}", path.display())),
+        }
+    }
+
+    fn key(&mut self, key: KeyEvent) {
+        if key.code == KeyCode::Esc
+            || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL))
+        {
+            self.quit = true;
+            return;
+        }
+        self.warning = None;
+
+        let answered = match &self.current_choice {
+            None => {
+                match key.code {
+                    KeyCode::Enter if !self.finished => self.advance_stage(),
+                    KeyCode::Char('n') if self.finished => self.reset(),
+                    KeyCode::Char('p') if self.finished => self.write_sheet(),
+                    _ => {}
+                }
+                return;
+            }
+            Some(Choice::Selection(s)) => match key.code {
+                KeyCode::Up => {
+                    self.cursor = self.cursor.saturating_sub(1);
+                    false
+                }
+                KeyCode::Down => {
+                    self.cursor = (self.cursor + 1).min(s.options.len().saturating_sub(1));
+                    false
+                }
+                KeyCode::Enter => {
+                    s.chosen.set(self.cursor);
+                    true
+                }
+                _ => false,
+            },
+            Some(Choice::Question(q)) => match key.code {
+                KeyCode::Char('y') => {
+                    q.chosen.set(true);
+                    true
+                }
+                KeyCode::Char('n') => {
+                    q.chosen.set(false);
+                    true
+                }
+                KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right => {
+                    self.cursor = 1 - self.cursor.min(1);
+                    false
+                }
+                KeyCode::Enter => {
+                    q.chosen.set(self.cursor == 0);
+                    true
+                }
+                _ => false,
+            },
+            Some(Choice::String(t)) => {
+                let submitted = edit(&mut self.input, key, |c| !c.is_control());
+                if submitted {
+                    t.chosen.set(self.input.clone());
+                }
+                submitted
+            }
+            Some(Choice::PickRoll(p)) => {
+                if edit(&mut self.input, key, |c| c.is_ascii_digit() || c == '-') {
+                    let range = p.roll.range();
+                    // nothing typed means rolling as normal
+                    match self.input.parse::<i8>() {
+                        _ if self.input.is_empty() => {
+                            p.chosen.set(None);
+                            true
+                        }
+                        Ok(pick) if range.contains(&pick) => {
+                            p.chosen.set(Some(pick));
+                            true
+                        }
+                        _ => {
+                            self.warning = Some(format!(
+                                "pick from {} to {}, or nothing to roll",
+                                range.start(),
+                                range.end()
+                            ));
+                            false
+                        }
+                    }
+                } else {
+                    false
+                }
+            }
+            // the event that failed has already finished, so this just clears the error
+            Some(Choice::Error(_)) => key.code == KeyCode::Enter,
+        };
+
+        if answered {
+            self.next_choice();
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [sheet, sidebar] =
+            Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)])
+                .areas(frame.area());
+        sheet::draw(frame, sheet, self.backend());
+        self.draw_sidebar(frame, sidebar);
+    }
+}
+
+// types into a line of input, returning whether it was submitted
+fn edit(input: &mut String, key: KeyEvent, allowed: impl Fn(char) -> bool) -> bool {
+    match key.code {
+        KeyCode::Char(c) if allowed(c) => input.push(c),
+        KeyCode::Backspace => {
+            input.pop();
+        }
+        KeyCode::Enter => return true,
+        _ => {}
+    }
+    false
+}
+
+// copies a save that won't load beside it, eg. character.broken-1.sof, so saving the character
+// that replaces it doesn't lose it. A copy that's already there is used rather than made again.
+fn keep_aside(path: &Path, raw: &[u8]) -> std::io::Result<PathBuf> {
+    let mut n = 1;
+    loop {
+        let kept = path.with_extension(format!("broken-{n}.sof"));
+        match std::fs::read(&kept) {
+            Ok(existing) if existing == raw => return Ok(kept),
+            Ok(_) => n += 1,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                std::fs::write(&kept, raw)?;
+                return Ok(kept);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn run(mut terminal: DefaultTerminal, mut app: App) -> std::io::Result<()> {
+    while !app.quit {
+        terminal.draw(|frame| app.draw(frame))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                app.key(key);
+            }
+        }
+    }
+    app.close();
+    Ok(())
+}
+
+fn main() -> std::io::Result<()> {
+    let save_path = std::env::args_os()
+        .nth(1)
+        .map_or_else(|| PathBuf::from(SAVE_FILE), PathBuf::from);
+    let app = App::new(save_path);
+    let terminal = ratatui::init();
+    let result = run(terminal, app);
+    ratatui::restore();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+    use sof_chargen::BirthOmen;
+
+    fn press(app: &mut App, code: KeyCode) {
+        app.key(KeyEvent::from(code));
+    }
+
+    fn screen(app: &App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content()
+            .chunks(buffer.area.width as usize)
+            .map(|line| line.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_chargen() {
+        let path = std::env::temp_dir().join(format!("sof-tui-{}.sof", std::process::id()));
+        let mut app = App::new(path.clone());
+        assert!(screen(&app).contains("Intro"));
+
+        // pressing enter takes the first option or the roll as it falls, all the way through
+        for _ in 0..1000 {
+            if app.finished {
+                break;
+            }
+            if let Some(Choice::Error(e)) = &app.current_choice {
+                panic!("{e}");
+            }
+            if let Some(Choice::String(_)) = &app.current_choice {
+                press(&mut app, KeyCode::Char('x'));
+            }
+            press(&mut app, KeyCode::Enter);
+        }
+        assert!(app.finished);
+        let finished = screen(&app);
+        assert!(finished.contains("Arms"), "{finished}");
+        assert!(finished.contains("You were born in"), "{finished}");
+
+        // and it's saved where the GUI would look for it
+        let saved = save::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            saved.get_character().careers,
+            app.backend().get_character().careers
+        );
+        assert_eq!(
+            *saved.log.read().unwrap(),
+            *app.backend().log.read().unwrap()
+        );
+
+        // the sheet goes next to the save
+        press(&mut app, KeyCode::Char('p'));
+        let sheet_path = app.sheet_path.clone().unwrap();
+        assert_eq!(sheet_path, path.with_extension("md"));
+        let sheet = std::fs::read_to_string(&sheet_path).unwrap();
+        assert!(sheet.contains("## Perk Slots"), "{sheet}");
+        assert!(screen(&app).contains("Sheet written to"));
+        std::fs::remove_file(sheet_path).unwrap();
+
+        // picking up the save carries on where it left off
+        let reloaded = App::new(path.clone());
+        press(&mut app, KeyCode::Char('n'));
+        assert!(!app.finished);
+        assert!(app.backend().get_character().omen.is_none());
+        assert!(reloaded.backend().get_character().omen.is_some());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_broken_save() {
+        let dir = std::env::temp_dir().join(format!("sof-tui-broken-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("character.sof");
+        std::fs::write(&path, b"not a save").unwrap();
+
+        // opening it and quitting straight away still leaves it on disk
+        for _ in 0..2 {
+            let mut app = App::new(path.clone());
+            assert!(
+                app.warning
+                    .as_ref()
+                    .unwrap()
+                    .contains("character.broken-1.sof")
+            );
+            press(&mut app, KeyCode::Esc);
+            assert!(app.quit);
+            app.close();
+            std::fs::write(&path, b"not a save").unwrap();
+        }
+        let kept = std::fs::read(dir.join("character.broken-1.sof")).unwrap();
+        assert_eq!(kept, b"not a save");
+        assert!(!dir.join("character.broken-2.sof").exists());
+
+        // the blank character that replaced it is saved as usual
+        std::fs::write(&path, b"not a save either").unwrap();
+        let mut app = App::new(path.clone());
+        app.close();
+        assert!(save::from_str(&std::fs::read_to_string(&path).unwrap()).is_ok());
+        let kept = std::fs::read(dir.join("character.broken-2.sof")).unwrap();
+        assert_eq!(kept, b"not a save either");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_pick_roll() {
+        let path = std::env::temp_dir().join(format!("sof-tui-pick-{}.sof", std::process::id()));
+        let mut app = App::new(path);
+        let backend = app.session.backend().clone();
+        backend.set_omen(BirthOmen::PropheticSigns(2));
+        app.session
+            .run_event(sof_chargen::event::test_pick_dice(backend));
+        app.next_choice();
+        assert!(matches!(app.current_choice, Some(Choice::PickRoll(_))));
+
+        for c in "200".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        assert!(app.warning.is_some());
+        assert!(matches!(app.current_choice, Some(Choice::PickRoll(_))));
+
+        press(&mut app, KeyCode::Backspace);
+        press(&mut app, KeyCode::Backspace);
+        press(&mut app, KeyCode::Enter);
+        assert!(app.warning.is_none());
+        assert!(!matches!(app.current_choice, Some(Choice::PickRoll(_))));
+        assert!(matches!(
+            app.backend().get_omen(),
+            Some(BirthOmen::PropheticSigns(1))
+        ));
+    }
+}
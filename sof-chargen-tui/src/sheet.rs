@@ -0,0 +1,93 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Stylize;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph};
+use sof_chargen::Stat::{Luck, Magic, Stamina};
+use sof_chargen::{Backend, CORE_STATS, Character, LogBackend, Stat};
+use std::fmt::Display;
+
+fn render(thing: Option<impl Display>) -> String {
+    match thing {
+        None => "-".to_owned(),
+        Some(s) => s.to_string(),
+    }
+}
+
+fn present_spans(name: &str, thing: Option<impl Display>) -> [Span<'static>; 3] {
+    [
+        Span::from(name.to_owned()).bold(),
+        Span::from(": "),
+        Span::from(render(thing)),
+    ]
+}
+
+fn present(name: &str, thing: Option<impl Display>) -> Line<'static> {
+    Line::from(present_spans(name, thing).to_vec())
+}
+
+fn stat_column(char: &Character, stat: Stat) -> Paragraph<'static> {
+    let line = |stat: Stat| format!("{:<9}{:>3}", stat, render(char.stats[stat]));
+    let mut lines = vec![Line::from(line(stat)).bold()];
+    lines.extend(stat.subskills().into_iter().map(|s| Line::from(line(s))));
+    Paragraph::new(lines)
+}
+
+pub fn draw(frame: &mut Frame, area: Rect, backend: &LogBackend) {
+    let char = backend.get_character();
+    let name = if char.name.is_empty() {
+        "Unnamed"
+    } else {
+        &char.name
+    };
+    let block = Block::bordered().title(format!(" {name} "));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let careers = (!char.careers.is_empty()).then(|| {
+        char.careers
+            .iter()
+            .map(|career| career.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    });
+    let details = Paragraph::new(vec![
+        Line::from(
+            [Stamina, Magic, Luck]
+                .into_iter()
+                .flat_map(|stat| {
+                    let [name, colon, value] = present_spans(&stat.to_string(), char.stats[stat]);
+                    [name, colon, value, Span::from("   ")]
+                })
+                .collect::<Vec<_>>(),
+        ),
+        present("Born", char.birth_location.as_ref().map(|l| &l.name)),
+        // until your omen is picked, you're shown as following your guardians
+        present("Culture", char.culture.or(char.guardians.culture)),
+        present("Faith", char.faith.or(char.guardians.faith)),
+        present("Omen", char.omen),
+        present("Affiliation", char.affiliation),
+        present("Rank", char.rank),
+        present("Careers", careers),
+    ]);
+
+    let [details_area, stats_area, traits_area] = Layout::vertical([
+        Constraint::Length(9),
+        Constraint::Length(4),
+        Constraint::Min(0),
+    ])
+    .spacing(1)
+    .areas(inner);
+    frame.render_widget(details, details_area);
+
+    let columns = Layout::horizontal([Constraint::Ratio(1, 5); 5]).split(stats_area);
+    for (stat, column) in CORE_STATS.into_iter().zip(columns.iter()) {
+        frame.render_widget(stat_column(&char, stat), *column);
+    }
+
+    if !char.traits.is_empty() {
+        let mut lines = vec![Line::from("Traits").bold()];
+        lines.extend(char.traits.iter().map(|t| Line::from(format!("- {t}"))));
+        frame.render_widget(Paragraph::new(lines), traits_area);
+    }
+}
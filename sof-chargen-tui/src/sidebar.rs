@@ -0,0 +1,132 @@
+use crate::App;
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use sof_chargen::Backend;
+use sof_chargen::ipc::Choice;
+
+// the options for a choice, with the one under the cursor highlighted
+fn options<'a>(options: impl IntoIterator<Item = &'a str>, cursor: usize) -> Vec<Line<'a>> {
+    options
+        .into_iter()
+        .enumerate()
+        .map(|(i, option)| {
+            if i == cursor {
+                Line::from(format!("> {option}")).reversed()
+            } else {
+                Line::from(format!("  {option}"))
+            }
+        })
+        .collect()
+}
+
+// as many of the log's last lines as fit, once they've wrapped
+fn log_tail(log: &str, width: u16, height: u16) -> Vec<Line<'_>> {
+    let width = usize::from(width.max(1));
+    let mut used = 0;
+    let mut lines: Vec<_> = log
+        .lines()
+        .rev()
+        .take_while(|line| {
+            used += line.chars().count().div_ceil(width).max(1);
+            used <= usize::from(height)
+        })
+        .map(Line::from)
+        .collect();
+    lines.reverse();
+    lines
+}
+
+impl App {
+    fn choice_input<'a>(&'a self, choice: &'a Choice) -> (Vec<Line<'a>>, &'static str) {
+        let mut lines = vec![Line::from(choice.description().to_owned()).bold()];
+        let hint = match choice {
+            Choice::Selection(s) => {
+                lines.extend(options(
+                    s.options.iter().map(|o| o.description.as_str()),
+                    self.cursor,
+                ));
+                "up/down to move, enter to choose"
+            }
+            Choice::Question(_) => {
+                lines.extend(options(["Yes", "No"], self.cursor));
+                "y or n, or up/down and enter"
+            }
+            Choice::String(_) => {
+                lines.push(Line::from(format!("> {}_", self.input)));
+                "enter to submit"
+            }
+            Choice::PickRoll(p) => {
+                let range = p.roll.range();
+                lines.push(Line::from(format!(
+                    "Rolling {}, from {} to {}",
+                    p.roll.render(),
+                    range.start(),
+                    range.end()
+                )));
+                lines.push(Line::from(format!("Pick: {}_", self.input)));
+                "type a number and enter to pick it, or just enter to roll"
+            }
+            Choice::Error(e) => {
+                lines.push(Line::from(e.to_string()).red());
+                "enter to dismiss"
+            }
+        };
+        (lines, hint)
+    }
+
+    fn prompt(&self) -> (Vec<Line<'_>>, &'static str) {
+        match &self.current_choice {
+            Some(choice) => self.choice_input(choice),
+            None if self.finished => {
+                let mut lines = vec![Line::from(format!(
+                    "Chargen is finished, and saved to {}.",
+                    self.save_path.display()
+                ))];
+                if let Some(path) = &self.sheet_path {
+                    lines.push(Line::from(format!("Sheet written to {}.", path.display())));
+                }
+                (lines, "n for a new character, p to write out a sheet")
+            }
+            None => (
+                vec![Line::from(format!(
+                    "Current life stage: {:?}",
+                    self.backend().get_character().life_stage
+                ))],
+                "enter to advance",
+            ),
+        }
+    }
+
+    pub(crate) fn draw_sidebar(&self, frame: &mut Frame, area: Rect) {
+        let (mut lines, hint) = self.prompt();
+        if let Some(warning) = &self.warning {
+            lines.push(Line::from(warning.clone()).red());
+        }
+        lines.push(Line::from(format!("{hint}, esc to quit")).dim());
+
+        // borders, plus a bit of room for long lines to wrap
+        let height = (lines.len() + 4).min(usize::from(area.height) * 2 / 3) as u16;
+        let [log_area, prompt_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(height)]).areas(area);
+
+        let log_block = Block::bordered().title(" Log ");
+        let inner = log_block.inner(log_area);
+        let log = self.backend().log.read().unwrap();
+        frame.render_widget(
+            Paragraph::new(log_tail(&log, inner.width, inner.height))
+                .wrap(Wrap { trim: false })
+                .block(log_block),
+            log_area,
+        );
+
+        frame.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered()),
+            prompt_area,
+        );
+    }
+}
@@ -1,13 +1,16 @@
-use crate::character::{BirthOmen, Character, Stat};
+use crate::ChargenError;
+use crate::character::{BirthOmen, Character, Stat, Subject};
 use crate::data::careers::{Affiliation, Career};
 use crate::data::locations::{Culture, Faith, Location};
 use crate::data::perks::Perk;
 use crate::dice::DiceRoll;
 use crate::event::stages::LifeStage;
-use std::cell::RefCell;
+use crate::sheet::{self, SheetFormat};
 use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, RwLock};
 
-pub trait Backend {
+// backends are shared between a session and the events it's running, which may be on any thread
+pub trait Backend: Send + Sync {
     fn get_character_mut(&self) -> impl DerefMut<Target = Character>;
     fn get_character(&self) -> impl Deref<Target = Character>;
 
@@ -28,44 +31,75 @@ pub trait Backend {
         self.get_character_mut().omen = Some(omen)
     }
 
-    fn gain_perk(&self, _perk: Perk) {
-        todo!("generic impl")
+    fn gain_perk(&self, _perk: Perk) -> Result<(), ChargenError> {
+        Err(ChargenError::Unsupported("gaining perks"))
     }
 
     fn set_birth_location(&self, location: Location) {
         self.log(format!("You were born in {}.", location.name));
         self.get_character_mut().birth_location = Some(location)
     }
-    fn set_culture(&self, culture: Culture) {
-        self.log(format!("You were raised {}.", culture));
-        self.get_character_mut().culture = Some(culture)
-    }
-    fn set_faith(&self, faith: Faith) {
-        self.log(match self.get_character().life_stage {
-            LifeStage::RollStats => format!("Your parents worshipped {}.", faith),
-            LifeStage::RollParents => format!("Your parents converted to {}.", faith),
-            LifeStage::RollOmens => format!(
-                "For the sake of your apprenticeship, you were raised to follow {}.",
-                faith
-            ),
-            _ => format!("You converted to {}.", faith),
+    fn set_culture(&self, subject: Subject, culture: Culture) {
+        match subject {
+            Subject::Guardians => {
+                self.log(format!("Your guardians were {}.", culture));
+                self.get_character_mut().guardians.culture = Some(culture)
+            }
+            Subject::Character => {
+                self.log(format!("You were raised {}.", culture));
+                self.get_character_mut().culture = Some(culture)
+            }
+        }
+    }
+    fn set_faith(&self, subject: Subject, faith: Faith) {
+        let old_faith = self.get_character().faith_of(subject);
+        self.log(match (subject, old_faith) {
+            (Subject::Guardians, None) => format!("Your guardians worshipped {}.", faith),
+            (Subject::Guardians, _) => format!("Your guardians converted to {}.", faith),
+            (Subject::Character, None) => format!("You were raised to follow {}.", faith),
+            (Subject::Character, _) => match self.get_character().life_stage {
+                LifeStage::RollOmens => format!(
+                    "For the sake of your apprenticeship, you were raised to follow {}.",
+                    faith
+                ),
+                _ => format!("You converted to {}.", faith),
+            },
         });
-        self.get_character_mut().faith = Some(faith)
+        match subject {
+            Subject::Guardians => self.get_character_mut().guardians.faith = Some(faith),
+            Subject::Character => self.get_character_mut().faith = Some(faith),
+        }
     }
 
-    fn set_affiliation(&self, affiliation: Affiliation) {
-        match self.get_character().affiliation {
-            None => self.log(format!("Your parents were members of the {}.", affiliation)),
-            Some(old) if old != affiliation => self.log(format!("You joined the {}.", affiliation)),
+    fn set_affiliation(&self, subject: Subject, affiliation: Affiliation) {
+        match (subject, self.get_character().affiliation_of(subject)) {
+            (Subject::Guardians, None) => self.log(format!(
+                "Your guardians were members of the {}.",
+                affiliation
+            )),
+            (Subject::Guardians, Some(old)) if old != affiliation => {
+                self.log(format!("Your guardians joined the {}.", affiliation))
+            }
+            (Subject::Character, None) => {
+                self.log(format!("You grew up among the {}.", affiliation))
+            }
+            (Subject::Character, Some(old)) if old != affiliation => {
+                self.log(format!("You joined the {}.", affiliation))
+            }
             _ => {}
         }
 
-        self.get_character_mut().affiliation = Some(affiliation)
+        match subject {
+            Subject::Guardians => {
+                self.get_character_mut().guardians.affiliation = Some(affiliation)
+            }
+            Subject::Character => self.get_character_mut().affiliation = Some(affiliation),
+        }
     }
-    fn set_career(&self, career: Career) {
-        if self.get_character().parents_career.is_none() {
-            self.log(format!("Your parents were {}s.", career.name));
-            self.get_character_mut().parents_career = Some(career);
+    fn set_career(&self, subject: Subject, career: Career) {
+        if subject == Subject::Guardians {
+            self.log(format!("Your guardians were {}s.", career.name()));
+            self.get_character_mut().guardians.career = Some(career);
             return;
         }
 
@@ -76,35 +110,47 @@ pub trait Backend {
         self.log(match num_careers {
             0 => format!(
                 "You were apprenticed as a {}, granting you the skills of a {}.",
-                career.name, career.class
+                career.name(),
+                career.class()
             ),
             _ if already_present => format!(
                 "You continued working as a {}, granting you the skills of a master {}.",
-                career.name, career.class
+                career.name(),
+                career.class()
             ),
             _ => format!(
                 "You spent a time working as a {}, granting you the skills of a {}.",
-                career.name, career.class
+                career.name(),
+                career.class()
             ),
         });
         // todo in character creation 3.0, gain a perk with that career name instead
         self.get_character_mut().careers.push(career);
     }
-    fn set_rank(&self, rank: i8) {
-        let old_rank = self.get_character().rank;
+    fn set_rank(&self, subject: Subject, rank: i8) {
+        let old_rank = self.get_character().rank_of(subject);
+        let whose = match subject {
+            Subject::Guardians => "Your guardians",
+            Subject::Character => "You",
+        };
         match old_rank {
-            None => self.log(format!("Your parents lived life at rank {}.", rank)),
+            None => match subject {
+                Subject::Guardians => self.log(format!("{whose} lived life at rank {}.", rank)),
+                Subject::Character => self.log(format!("{whose} started life at rank {}.", rank)),
+            },
             Some(i) => match rank - i {
                 0 => {}
-                1 => self.log("You gained a rank.".to_string()),
-                -1 => self.log("You fell a rank.".to_string()),
-                x if x > 0 => self.log(format!("You gained {} ranks.", x)),
-                x if x < 0 => self.log(format!("You fell {} ranks.", -x)),
-                _ => unreachable!(),
+                1 => self.log(format!("{whose} gained a rank.")),
+                -1 => self.log(format!("{whose} fell a rank.")),
+                x if x > 0 => self.log(format!("{whose} gained {} ranks.", x)),
+                x => self.log(format!("{whose} fell {} ranks.", -x)),
             },
         }
         let rank = rank.clamp(0, 9);
-        self.get_character_mut().rank = Some(rank);
+        match subject {
+            Subject::Guardians => self.get_character_mut().guardians.rank = Some(rank),
+            Subject::Character => self.get_character_mut().rank = Some(rank),
+        }
     }
 
     fn log(&self, text: String) {
@@ -112,19 +158,105 @@ pub trait Backend {
     }
 }
 
+// a handle to a backend is a backend too, so events can own whatever they were handed:
+// a borrow for as long as the caller likes, or an Arc to send them off elsewhere
+macro_rules! forward_backend {
+    ($($handle:ty),*) => {$(
+        impl<T: Backend> Backend for $handle {
+            fn get_character_mut(&self) -> impl DerefMut<Target = Character> {
+                (**self).get_character_mut()
+            }
+            fn get_character(&self) -> impl Deref<Target = Character> {
+                (**self).get_character()
+            }
+            fn set_stat(&self, stat: Stat, roll: &impl DiceRoll) {
+                (**self).set_stat(stat, roll)
+            }
+            fn get_stat(&self, stat: Stat) -> Option<i8> {
+                (**self).get_stat(stat)
+            }
+            fn gain_trait(&self, description: String) {
+                (**self).gain_trait(description)
+            }
+            fn get_omen(&self) -> Option<BirthOmen> {
+                (**self).get_omen()
+            }
+            fn set_omen(&self, omen: BirthOmen) {
+                (**self).set_omen(omen)
+            }
+            fn gain_perk(&self, perk: Perk) -> Result<(), ChargenError> {
+                (**self).gain_perk(perk)
+            }
+            fn set_birth_location(&self, location: Location) {
+                (**self).set_birth_location(location)
+            }
+            fn set_culture(&self, subject: Subject, culture: Culture) {
+                (**self).set_culture(subject, culture)
+            }
+            fn set_faith(&self, subject: Subject, faith: Faith) {
+                (**self).set_faith(subject, faith)
+            }
+            fn set_affiliation(&self, subject: Subject, affiliation: Affiliation) {
+                (**self).set_affiliation(subject, affiliation)
+            }
+            fn set_career(&self, subject: Subject, career: Career) {
+                (**self).set_career(subject, career)
+            }
+            fn set_rank(&self, subject: Subject, rank: i8) {
+                (**self).set_rank(subject, rank)
+            }
+            fn log(&self, text: String) {
+                (**self).log(text)
+            }
+        }
+    )*};
+}
+
+forward_backend!(&T, Arc<T>);
+
 // the backend contract effectively requires interior mutability
-// in theory this could be an UnsafeCell since we never return references to character
 #[derive(Debug, Default)]
 pub struct BaseBackend {
-    pub character: RefCell<Character>,
+    pub character: RwLock<Character>,
 }
 
 impl Backend for BaseBackend {
     fn get_character_mut(&self) -> impl DerefMut<Target = Character> {
-        self.character.borrow_mut()
+        self.character.write().unwrap()
+    }
+
+    fn get_character(&self) -> impl Deref<Target = Character> {
+        self.character.read().unwrap()
+    }
+}
+
+/// A backend that keeps its log rather than printing it. This, log and all, is what the front-ends
+/// save as `character.sof`.
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct LogBackend {
+    pub character: RwLock<Character>,
+    pub log: RwLock<String>,
+}
+
+impl LogBackend {
+    /// The character's sheet, with everything logged so far as their life
+    pub fn sheet(&self, format: SheetFormat) -> String {
+        sheet::render(&self.get_character(), &self.log.read().unwrap(), format)
+    }
+}
+
+impl Backend for LogBackend {
+    fn get_character_mut(&self) -> impl DerefMut<Target = Character> {
+        self.character.write().unwrap()
     }
 
     fn get_character(&self) -> impl Deref<Target = Character> {
-        self.character.borrow()
+        self.character.read().unwrap()
+    }
+
+    fn log(&self, text: String) {
+        let mut log = self.log.write().unwrap();
+        log.push('\n');
+        log.push_str(&text);
     }
 }
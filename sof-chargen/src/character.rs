@@ -8,6 +8,7 @@ use crate::event::stages::LifeStage;
 use enum_map::EnumMap;
 use std::fmt;
 use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 
 // spans the numbers 0..=100
 // in some cases only 1..=100 are valid, though
@@ -68,7 +69,7 @@ impl Stat {
     }
 }
 
-#[derive(Debug, Copy, Clone, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
 pub enum BirthOmen {
     ProsperousConstellations,
     PropheticSigns(usize),
@@ -98,8 +99,68 @@ impl Display for BirthOmen {
     }
 }
 
+impl BirthOmen {
+    /// Whether they're the same omen, however many charges of prophetic signs are left
+    pub fn same_as(&self, other: &BirthOmen) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+// the omen as it's offered at birth, from its name or enough of it to tell it apart, eg. "shooting"
+impl FromStr for BirthOmen {
+    type Err = String;
+
+    fn from_str(given: &str) -> Result<Self, Self::Err> {
+        let s = given.trim().to_lowercase();
+        // prophetic signs is named without its charges
+        let name = |omen: &BirthOmen| match omen {
+            BirthOmen::PropheticSigns(_) => BirthOmen::PropheticSigns(0),
+            omen => *omen,
+        };
+        let names = BIRTH_OMENS.map(|omen| name(&omen).to_string());
+        if let Some(i) = names.iter().position(|name| name.to_lowercase() == s) {
+            return Ok(BIRTH_OMENS[i]);
+        }
+        let matches: Vec<_> = (0..BIRTH_OMENS.len())
+            .filter(|&i| !s.is_empty() && names[i].to_lowercase().starts_with(&s))
+            .collect();
+        match matches[..] {
+            [i] => Ok(BIRTH_OMENS[i]),
+            [] => Err(format!(
+                "expected one of {}, not \"{given}\"",
+                names.join(", ")
+            )),
+            _ => Err(format!(
+                "\"{given}\" could be any of {}",
+                matches
+                    .iter()
+                    .map(|&i| names[i].as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+}
+
+/// Whose standing an event is rolling for: your guardians' during birth, your own afterwards
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Subject {
+    Guardians,
+    Character,
+}
+
+/// The people who raised you. Rolled before your birth omen, which then decides how much of
+/// their standing you inherit.
+#[derive(Debug, Default, Clone, serde::Deserialize, serde::Serialize)]
+pub struct Guardians {
+    pub affiliation: Option<Affiliation>,
+    pub rank: Option<DiceT>,
+    pub career: Option<Career>,
+    pub culture: Option<Culture>,
+    pub faith: Option<Faith>,
+}
+
 #[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
-#[serde(bound(deserialize = "'de: 'static"))]
 pub struct Character {
     pub stats: EnumMap<Stat, Option<DiceT>>,
     pub name: String,
@@ -112,12 +173,42 @@ pub struct Character {
     pub faith: Option<Faith>,
 
     pub affiliation: Option<Affiliation>,
-    pub parents_career: Option<Career>,
     pub careers: Vec<Career>, // you can have up to 4 careers i think
     pub rank: Option<DiceT>,
     pub life_stage: LifeStage,
+    #[serde(default)]
+    pub guardians: Guardians,
+}
+
+impl Character {
+    pub fn culture_of(&self, subject: Subject) -> Option<Culture> {
+        match subject {
+            Subject::Guardians => self.guardians.culture,
+            Subject::Character => self.culture,
+        }
+    }
+    pub fn faith_of(&self, subject: Subject) -> Option<Faith> {
+        match subject {
+            Subject::Guardians => self.guardians.faith,
+            Subject::Character => self.faith,
+        }
+    }
+    pub fn affiliation_of(&self, subject: Subject) -> Option<Affiliation> {
+        match subject {
+            Subject::Guardians => self.guardians.affiliation,
+            Subject::Character => self.affiliation,
+        }
+    }
+    pub fn rank_of(&self, subject: Subject) -> Option<DiceT> {
+        match subject {
+            Subject::Guardians => self.guardians.rank,
+            Subject::Character => self.rank,
+        }
+    }
 }
 
+// not rolled during chargen yet, but it's how every check in play works
+#[allow(dead_code)]
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
 enum CheckResult {
     CriticalFailure = 0,
@@ -128,6 +219,7 @@ enum CheckResult {
     CriticalSuccess = 5,
 }
 
+#[allow(dead_code)]
 fn check(to_beat: u64, roll: u64) -> CheckResult {
     match roll {
         1..=5 => CriticalSuccess,
@@ -157,4 +249,28 @@ mod test {
         assert_eq!(check(50, 12), ExtremeSuccess);
         assert_ne!(check(100, crate::roll!(1 d 100).result() as u64), Failure);
     }
+
+    #[test]
+    fn test_save_by_id() {
+        let character = Character {
+            birth_location: crate::data::tables::bundled()
+                .location_by_id("duruza", crate::data::locations::Demographic::Urban),
+            careers: vec![Career::from_id("peddler").unwrap()],
+            ..Default::default()
+        };
+        let save = ron::to_string(&character).unwrap();
+        assert!(save.contains(r#"birth_location:Some((id:"duruza",demographic:Urban))"#));
+        assert!(save.contains(r#"careers:["peddler"]"#));
+
+        // a save borrows nothing from its text, so it can be loaded from a temporary
+        let loaded: Character = ron::from_str(&save.clone()).unwrap();
+        assert_eq!(
+            loaded.birth_location.unwrap().name,
+            "The Imperial Capital of Duruza"
+        );
+        assert_eq!(loaded.careers[0].name(), "Peddler");
+
+        let bad = save.replace("\"peddler\"", "\"nobody\"");
+        assert!(ron::from_str::<Character>(&bad).is_err());
+    }
 }
@@ -1,3 +1,8 @@
 pub mod careers;
+pub mod lint;
 pub mod locations;
+pub mod odds;
+pub mod origins;
 pub mod perks;
+pub mod roll_table;
+pub mod tables;
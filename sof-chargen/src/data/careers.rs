@@ -1,8 +1,12 @@
+use crate::ChargenError;
 use crate::data::careers::Affiliation::*;
-use crate::data::locations::{CareerTable, Culture, Demographic, Faith, Location};
+use crate::data::locations::{Culture, Faith, Location};
+use crate::data::odds::{Odds, odds};
+use crate::data::origins::{Origins, Target, origins};
+use crate::data::tables::{CareerInfo, bundled, career_list};
 use std::fmt::{Display, Formatter};
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, serde::Deserialize, serde::Serialize)]
 pub enum Affiliation {
     Slumfolk,
     Criminals,
@@ -16,13 +20,17 @@ pub enum Affiliation {
     Gentry,
 }
 
+pub const AFFILIATIONS: [Affiliation; 10] = [
+    Slumfolk, Criminals, Vagabonds, Peasantry, Performers, Plebeians, Watch, Army, Scholars, Gentry,
+];
+
 impl Display for Affiliation {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}", self) // all affiliations are one word, so debug and display are the same
     }
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, serde::Deserialize, serde::Serialize)]
 pub enum CareerClass {
     Artisan,
     Beggar,
@@ -100,27 +108,84 @@ impl CareerClass {
     }
 }
 
-#[derive(Copy, Clone, Debug, serde::Deserialize, serde::Serialize, Eq, PartialEq)]
-pub struct Career {
-    pub name: &'static str,
-    pub class: CareerClass,
+/// A career from career_list.ron. Careers are saved and written in the tables by id, so their
+/// names can be fixed without breaking anything; `name` and `class` look the rest up.
+#[derive(Copy, Clone, Debug, serde::Deserialize, serde::Serialize, Eq, PartialEq, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct Career(u16); // index into the career list
+
+impl Career {
+    /// The career with this id, if there is one
+    pub fn from_id(id: &str) -> Option<Career> {
+        career_list()
+            .iter()
+            .position(|info| info.id == id)
+            .map(|i| Career(i as u16))
+    }
+
+    /// Every career there is
+    pub fn all() -> impl Iterator<Item = Career> {
+        (0..career_list().len()).map(|i| Career(i as u16))
+    }
+
+    fn info(&self) -> &'static CareerInfo {
+        &career_list()[self.0 as usize]
+    }
+
+    pub fn id(&self) -> &'static str {
+        &self.info().id
+    }
+
+    pub fn name(&self) -> &'static str {
+        &self.info().name
+    }
+
+    pub fn class(&self) -> CareerClass {
+        self.info().class
+    }
+}
+
+impl TryFrom<String> for Career {
+    type Error = String;
+
+    fn try_from(id: String) -> Result<Self, Self::Error> {
+        Career::from_id(&id).ok_or_else(|| format!("there's no career with the id \"{id}\""))
+    }
+}
+
+impl From<Career> for String {
+    fn from(career: Career) -> Self {
+        career.id().to_string()
+    }
 }
 
 impl Display for Career {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} ({})", self.name, self.class.letter())
+        write!(f, "{} ({})", self.name(), self.class().letter())
     }
 }
 
-#[derive(Copy, Eq, PartialEq, Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Copy, Eq, PartialEq, Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
 pub enum CareerTableStar {
+    #[default]
     None,
     NeedsFaith(Faith),
     NeedsFaithAndCulture(Faith, Culture),
 }
 
+impl Display for CareerTableStar {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CareerTableStar::None => write!(f, "no requirements"),
+            CareerTableStar::NeedsFaith(faith) => write!(f, "needs {faith}"),
+            CareerTableStar::NeedsFaithAndCulture(faith, culture) => {
+                write!(f, "needs {faith} and {culture} culture")
+            }
+        }
+    }
+}
+
 #[derive(Clone, Eq, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
-#[serde(bound(deserialize = "'de: 'static"))]
 pub enum CareerTableEntry {
     Career(Career, CareerTableStar),
     Careers((Career, CareerTableStar), (Career, CareerTableStar)),
@@ -130,524 +195,35 @@ pub enum CareerTableEntry {
     Reroll,
 }
 
-macro_rules! compute_affiliation {
-    (
-        $dice: ident,
-        $a: expr,
-        $b: expr,
-        $c: expr,
-        $d: expr,
-        $e: expr,
-        $f: expr,
-        $g: expr,
-        $h: expr,
-        $i: expr
-    ) => {
-        match $dice {
-            $a..=100 => Slumfolk,
-            $b..$a => Criminals,
-            $c..$b => Vagabonds,
-            $d..$c => Peasantry,
-            $e..$d => Performers,
-            $f..$e => Plebeians,
-            $g..$f => Watch,
-            $h..$g => Army,
-            $i..$h => Scholars,
-            1..$i => Gentry,
-            _ => unreachable!(),
-        }
-    };
-}
-
-macro_rules! career_class {
-    (A) => {
-        crate::data::careers::CareerClass::Artisan
-    };
-    (B) => {
-        crate::data::careers::CareerClass::Beggar
-    };
-    (C) => {
-        crate::data::careers::CareerClass::ConArtist
-    };
-    (D) => {
-        crate::data::careers::CareerClass::Detective
-    };
-    (E) => {
-        crate::data::careers::CareerClass::Entertainer
-    };
-    (F) => {
-        crate::data::careers::CareerClass::Farmer
-    };
-    (G) => {
-        crate::data::careers::CareerClass::Guard
-    };
-    (H) => {
-        crate::data::careers::CareerClass::Hunter
-    };
-    (I) => {
-        crate::data::careers::CareerClass::Infantry
-    };
-    (K) => {
-        crate::data::careers::CareerClass::Knight
-    };
-    (L) => {
-        crate::data::careers::CareerClass::Labourer
-    };
-    (M) => {
-        crate::data::careers::CareerClass::Mariner
-    };
-    (N) => {
-        crate::data::careers::CareerClass::Noble
-    };
-    (O) => {
-        crate::data::careers::CareerClass::Official
-    };
-    (P) => {
-        crate::data::careers::CareerClass::Physician
-    };
-    (R) => {
-        crate::data::careers::CareerClass::Rogue
-    };
-    (S) => {
-        crate::data::careers::CareerClass::Scholar
-    };
-    (T) => {
-        crate::data::careers::CareerClass::Trader
-    };
-    (W) => {
-        crate::data::careers::CareerClass::Wayfarer
-    };
-    (Z) => {
-        crate::data::careers::CareerClass::Zealot
-    };
+// the tables themselves live in careers.ron, see data::tables
+pub fn get_affiliation(location: &Location, d100: i8) -> Result<Affiliation, ChargenError> {
+    bundled().affiliation(location.career_table, location.demographic, d100)
 }
 
-macro_rules! career_inner {
-    ($($name:ident) * ($letter:ident)) => {
-        crate::data::careers::Career{name: stringify!($($name) *), class: career_class!($letter)}
-    };
+pub fn get_rank(location: &Location, affiliation: Affiliation, d6: i8) -> Result<i8, ChargenError> {
+    bundled().rank(location.career_table, affiliation, d6)
 }
 
-macro_rules! career {
-    ($($name:ident) * ($letter:ident)) => {
-        crate::data::careers::CareerTableEntry::Career(
-            career_inner!($($name) * ($letter)),
-            CareerTableStar::None,
-        )
-    };
-    ($($name1:ident) * ($letter1:ident), $($name2:ident) * ($letter2:ident)) => {
-        crate::data::careers::CareerTableEntry::Careers(
-            (career_inner!($($name1) * ($letter1)), CareerTableStar::None,),
-            (career_inner!($($name2) * ($letter2)), CareerTableStar::None,)
-        )
-    };
-    ($($name1:ident) * ($letter1:ident), *$($name2:ident) * ($letter2:ident) $star:expr) => {
-        crate::data::careers::CareerTableEntry::Careers(
-            (career_inner!($($name1) * ($letter1)), CareerTableStar::None,),
-            (career_inner!($($name2) * ($letter2)), $star,)
-        )
-    };
-    (Join $affiliation:ident) => {
-        crate::data::careers::CareerTableEntry::ChangeAffiliation(
-            crate::data::careers::Affiliation::$affiliation
-        )
-    };
-    (Remain at rank $rank:literal) => {
-        crate::data::careers::CareerTableEntry::RemainAtRank($rank)
-    };
-    (Reroll affiliation with disadvantage) => {
-        crate::data::careers::CareerTableEntry::RerollWithDisadvantage
-    };
-    (Reroll affiliation) => {
-        crate::data::careers::CareerTableEntry::Reroll
-    };
+pub fn get_careers(
+    location: &Location,
+    affiliation: Affiliation,
+    rank: i8,
+) -> Result<CareerTableEntry, ChargenError> {
+    bundled().careers(location.career_table, affiliation, rank)
 }
 
-pub const fn get_affiliation(location: &Location, d100: i8) -> Affiliation {
-    match location.career_table {
-        CareerTable::ValiantEmpire => match location.demographic {
-            Demographic::Urban => compute_affiliation!(d100, 91, 76, 71, 61, 51, 31, 27, 17, 11),
-            Demographic::Rural => compute_affiliation!(d100, 91, 86, 76, 46, 41, 31, 27, 17, 11),
-            Demographic::Border => compute_affiliation!(d100, 86, 81, 61, 51, 46, 36, 31, 16, 6),
-        },
-        CareerTable::Nivena => match location.demographic {
-            Demographic::Urban => compute_affiliation!(d100, 93, 84, 81, 76, 66, 46, 31, 21, 11),
-            Demographic::Rural => compute_affiliation!(d100, 93, 86, 76, 61, 51, 36, 31, 11, 6),
-            Demographic::Border => compute_affiliation!(d100, 91, 81, 56, 46, 36, 31, 26, 11, 4),
-        },
-        CareerTable::Marolaw => match location.demographic {
-            Demographic::Urban => compute_affiliation!(d100, 81, 71, 66, 61, 51, 36, 31, 21, 11),
-            Demographic::Rural => compute_affiliation!(d100, 81, 76, 71, 61, 46, 36, 26, 11, 6),
-            Demographic::Border => compute_affiliation!(d100, 86, 81, 61, 56, 46, 39, 30, 13, 4),
-        },
-    }
+/// The exact odds of each affiliation, rank and career for guardians of `culture` born here
+pub fn get_odds(location: &Location, culture: Culture) -> Result<Odds, ChargenError> {
+    odds(bundled(), location, culture)
 }
 
-pub const fn get_rank(location: &Location, affiliation: Affiliation, d6: i8) -> i8 {
-    let d3 = (d6 + 1) / 2; // rounds up so 1,2 -> 1, 3,4 -> 2, etc
-    match location.career_table {
-        CareerTable::ValiantEmpire => match affiliation {
-            Slumfolk => d3,
-            Criminals => d3,
-            Vagabonds => d3 + 1,
-            Peasantry => d3 + 1,
-            Performers => d3 + 1,
-            Plebeians => d6,
-            Watch => d3 + 2,
-            Army => d3 + 2,
-            Scholars => d6 + 1,
-            Gentry => d3 + 3,
-        },
-        CareerTable::Nivena => match affiliation {
-            Slumfolk => d3,
-            Criminals => d3,
-            Vagabonds => d3 + 1,
-            Peasantry => d3 + 1,
-            Performers => d3 + 1,
-            Plebeians => d3 + 2,
-            Watch => d3 + 2,
-            Army => d6 + 1,
-            Scholars => d3 + 3,
-            Gentry => d6 + 2,
-        },
-        CareerTable::Marolaw => match affiliation {
-            Slumfolk => d3 - 1,
-            Criminals => d3,
-            Vagabonds => d3,
-            Peasantry => d3 + 1,
-            Performers => d3 + 1,
-            Plebeians => d3 + 2,
-            Watch => d3 + 2,
-            Army => d3 + 3,
-            Scholars => d3 + 3,
-            Gentry => d3 + 5,
-        },
-    }
-}
-
-pub fn get_careers(location: &Location, affiliation: Affiliation, rank: i8) -> CareerTableEntry {
-    match location.career_table {
-        CareerTable::ValiantEmpire => match affiliation {
-            Slumfolk => match rank {
-                0 => career!(Outcast(B)),
-                1 => career!(Militia (G), Odd Jobber (L)),
-                2 => career!(Militia (G), Odd Jobber (L)),
-                3 => career!(Peddler(C), *Dewisetic(Z)
-                    CareerTableStar::NeedsFaithAndCulture(Faith::Gytungrug, Culture::Kremish)),
-                4..=9 => career!(Join Plebeians),
-                _ => unreachable!(),
-            },
-            Criminals => match rank {
-                0 => career!(Thief(R)),
-                1 => career!(Thief(R), Bodyguard(G)),
-                2 => career!(Sawbones(P), Smuggler(M)),
-                3 => career!(Counterfeiter(C), Headhunter(D)),
-                4 => career!(Fence(T), Gang Boss(K)),
-                5 => career!(Gang Boss(K)),
-                6..=9 => career!(Remain at rank 5),
-                _ => unreachable!(),
-            },
-            Vagabonds => match rank {
-                0 => career!(Deserter(R), Poacher(H)),
-                1 => career!(Deserter(R), Poacher(H)),
-                2 => career!(Smuggler(M), Highwayman(W)),
-                3 => career!(Sellsword(I), Highwayman(W)),
-                4 => career!(Sellsword(I), Sawbones(P)),
-                5 => career!(Field Captain(K), Baneman(D)),
-                6 => career!(Field Captain(K), Baneman(D)),
-                7..=9 => career!(Remain at rank 6),
-                _ => unreachable!(),
-            },
-            Peasantry => match rank {
-                0 => career!(Woodsman(H), Serf(L)),
-                1 => career!(Woodsman(H), Serf(L)),
-                2 => career!(Fisher(M), Mootman(F)),
-                3 => career!(Fisher(M), Mootman(F)),
-                4 => career!(Herder(T), Smallholder(F)),
-                5 => career!(Herder(T), Smallholder(F)),
-                6..=9 => career!(Remain at rank 5),
-                _ => unreachable!(),
-            },
-            Performers => match rank {
-                0 => career!(Penny Mummer(B)),
-                1 => career!(Penny Mummer(B)),
-                2 => career!(Mummer(E)),
-                3 => career!(Mummer(E)),
-                4 => career!(Herald(E), Proprietor(T)),
-                5 => career!(Proprietor(T)),
-                6..=9 => career!(Remain at rank 5),
-                _ => unreachable!(),
-            },
-            Plebeians => match rank {
-                0 => career!(Reroll affiliation with disadvantage),
-                1 => career!(Outcast(B)),
-                2 => career!(Peddler(C), Odd Jobber(L)),
-                3..=4 => career!(Courtesan(E), *Lodge Artisan(A)
-                    CareerTableStar::NeedsFaith(Faith::Accorder)),
-                5 => career!(Proprietor(T), Master Artisan(A)),
-                6 => career!(Proprietor(T), Master Artisan(A)),
-                7..=9 => career!(Remain at rank 6),
-                _ => unreachable!(),
-            },
-            Watch => match rank {
-                0..=1 => career!(Join Scholars),
-                2 => career!(Inquisitor(I)),
-                3 => career!(Inquisitor(I)),
-                4 => career!(Inquisition Seer(D), Bluecoat(H)),
-                5 => career!(Inquisition Seer(D), Bluecoat Seer(S)),
-                6 => career!(Sceptre Scribe(O), Temple Guardian(K)),
-                7 => career!(Sceptre Scribe(O), Temple Guardian(K)),
-                8..=9 => career!(Remain at rank 7),
-                _ => unreachable!(),
-            },
-            Army => match rank {
-                0..=1 => career!(Join Vagabonds),
-                2 => career!(Watch(G), Auxiliary(H)),
-                3 => career!(Sailor(M), Legionnaire(I)),
-                4 => career!(Medicus(P), Legionnaire(I)),
-                5 => career!(Medicus(P), Courier(W)),
-                6 => career!(Field Captain(K)),
-                7..=9 => career!(Remain at rank 6),
-                _ => unreachable!(),
-            },
-            Scholars => match rank {
-                0 => career!(Reroll affiliation with disadvantage),
-                1 => career!(Mendicant(B)),
-                2 => career!(Mendicant(B), Apothecary(P)),
-                3 => career!(Cleric(Z), Apothecary(P)),
-                4 => career!(Cleric(Z), Temple Seer(S)),
-                5 => career!(Cleric(Z), Temple Seer(S)),
-                6 => career!(Sceptre Scribe(O)),
-                7 => career!(Sceptre Scribe(O)),
-                8..=9 => career!(Remain at rank 7),
-                _ => unreachable!(),
-            },
-            Gentry => match rank {
-                0..=3 => career!(Reroll affiliation),
-                4 => career!(Prefect(O)),
-                5 => career!(Prefect(O), *Lodge Officiary(Z)
-                    CareerTableStar::NeedsFaith(Faith::Accorder)),
-                6 => career!(Courtier(O), *Lodge Officiary(Z)
-                    CareerTableStar::NeedsFaith(Faith::Accorder)),
-                7 => career!(Grandee(N), Centurion(K)),
-                8 => career!(Grandee(N), Centurion(K)),
-                9 => career!(Liege Lord(N)),
-                _ => unreachable!(),
-            },
-        },
-        CareerTable::Nivena => match affiliation {
-            Slumfolk => match rank {
-                0 => career!(Outcast(B)),
-                1 => career!(Militia(G), Odd Jobber(L)),
-                2 => career!(Militia(G), Odd Jobber(L)),
-                3 => career!(Peddler(C), *Dewisetic(Z)
-                    CareerTableStar::NeedsFaithAndCulture(Faith::Gytungrug, Culture::Kremish)),
-                4..=9 => career!(Join Plebeians),
-                _ => unreachable!(),
-            },
-            Criminals => match rank {
-                0 => career!(Thief(R)),
-                1 => career!(Thief(R), Bodyguard(G)),
-                2 => career!(Sawbones(P), Smuggler(M)),
-                3 => career!(Counterfeiter(C), Headhunter(D)),
-                4 => career!(Fence(T), Gang Boss(K)),
-                5 => career!(Gang Boss(K)),
-                6..=9 => career!(Remain at rank 6),
-                _ => unreachable!(),
-            },
-            Vagabonds => match rank {
-                0 => career!(Deserter(R), Woodsman(H)),
-                1 => career!(Deserter(R), Woodsman(H)),
-                2 => career!(Smuggler(M), Highwayman(W)),
-                3 => career!(Sellsword(I), Highwayman(W)),
-                4 => career!(Sellsword(I), Sawbones(P)),
-                5 => career!(Field Captain(K), Baneman(D)),
-                6 => career!(Field Captain(K), Baneman(D)),
-                7..=9 => career!(Remain at rank 6),
-                _ => unreachable!(),
-            },
-            Peasantry => match rank {
-                0 => career!(Woodsman(H), Serf(L)),
-                1 => career!(Woodsman(H), Serf(L)),
-                2 => career!(Fisher(M), Mootman(F)),
-                3 => career!(Fisher(M), Mootman(F)),
-                4 => career!(Herder(T), Smallholder(F)),
-                5 => career!(Herder(T), Smallholder(F)),
-                6..=9 => career!(Remain at rank 6),
-                _ => unreachable!(),
-            },
-            Performers => match rank {
-                0 => career!(Penny Mummer(B)),
-                1 => career!(Lodge Apprentice(L), Bodyguard(G)),
-                2 => career!(Mummer(E), Bodyguard(G)),
-                3 => career!(Mummer(E), Lodge Merchant(T)),
-                4 => career!(Herald(E), Lodge Merchant(T)),
-                5 => career!(Lodge Officiary(Z)),
-                6..=9 => career!(Remain at rank 6),
-                _ => unreachable!(),
-            },
-            Plebeians => match rank {
-                0..=1 => career!(Reroll affiliation with disadvantage),
-                2 => career!(Bodyguard(G), Lodge Apprentice(L)),
-                3 => career!(Bodyguard(G), Lodge Apprentice(L)),
-                4 => career!(Lodge Merchant(T), Lodge Artisan(A)),
-                5 => career!(Lodge Artisan(A), Lodge Officiary(Z)),
-                6 => career!(Lodge Officiary(Z)),
-                7..=9 => career!(Remain at rank 6),
-                _ => unreachable!(),
-            },
-            Watch => match rank {
-                0..=1 => career!(Join Criminals),
-                2 => career!(Watch(G)),
-                3 => career!(Watch(G), Courier(W)),
-                4 => career!(Watch Sergeant(D), Pale Agent(R)),
-                5 => career!(Courtier(O), Pale Agent(R)),
-                6 => career!(Courtier(O), Pale Sergeant(C)),
-                7..=9 => career!(Remain at rank 6),
-                _ => unreachable!(),
-            },
-            Army => match rank {
-                0..=1 => career!(Join Vagabonds),
-                2 => career!(Militia(G), Spotter(R)),
-                3 => career!(Legionary(I), Archer(W)),
-                4 => career!(Legionary(I), Archer(W)),
-                5 => career!(Landed Veteran(F), Prefect(O)),
-                6 => career!(Centurion(K), Prefect(O)),
-                7 => career!(Centurion(K), Senator(N)),
-                8..=9 => career!(Remain at rank 7),
-                _ => unreachable!(),
-            },
-            Scholars => match rank {
-                0..=2 => career!(Reroll affiliation),
-                3 => career!(Medicus(P)),
-                4 => career!(Medicus(P), Lodge Scribe(O)),
-                5 => career!(Lodge Scribe(O), Lodge Scholar(S)),
-                6 => career!(Lodge Officiary(Z), Lodge Scholar(S)),
-                7 => career!(Lodge Officiary(Z)),
-                8..=9 => career!(Remain at rank 7),
-                _ => unreachable!(),
-            },
-            Gentry => match rank {
-                0..=1 => career!(Reroll affiliation with disadvantage),
-                2 => career!(Bodyguard(G)),
-                3 => career!(Bodyguard(G), SaIlor(M)),
-                4 => career!(Caravan Guard(W), Sailor(M)),
-                5 => career!(Caravan Guard(W), Lodge Merchant(T)),
-                6 => career!(Lodge Merchant(T)),
-                7 => career!(Lodge Merchant(T), Lodge Officiary(Z)),
-                8 => career!(Lodge Officiary(Z)),
-                9 => career!(Remain at rank 8),
-                _ => unreachable!(),
-            },
-        },
-
-        CareerTable::Marolaw => match affiliation {
-            Slumfolk => match rank {
-                0 => career!(Üslings(B), Clan Thrall(L)),
-                1 => career!(Galley Thrall(M), Clan Thrall(L)),
-                2 => career!(Galley Thrall(M), Peddler(C)),
-                3 => career!(March Thrall(F), Courtesan(E)),
-                4..=9 => career!(Join Criminals),
-                _ => unreachable!(),
-            },
-            Criminals => match rank {
-                0 => career!(Thief(R)),
-                1 => career!(Thief(R), Bodyguard(G)),
-                2 => career!(Sawbones(P), Smuggler(M)),
-                3 => career!(Counterfeiter(C), Headhunter(D)),
-                4 => career!(Fence(T), Gang Boss(K)),
-                5 => career!(Gang Boss(K)),
-                6..=9 => career!(Remain at rank 5),
-                _ => unreachable!(),
-            },
-            Vagabonds => match rank {
-                0 => career!(Deserter(R), Poacher(H)),
-                1 => career!(Deserter(R), Poacher(H)),
-                2 => career!(Smuggler(M), Highwayman(W)),
-                3 => career!(Sellsword(I), Highwayman(W)),
-                4 => career!(Sellsword(I), Sawbones(P)),
-                5 => career!(Field Captain(K), Baneman(D)),
-                6 => career!(Field Captain(K), Baneman(D)),
-                7..=9 => career!(Join Gentry),
-                _ => unreachable!(),
-            },
-            Peasantry => match rank {
-                0 => career!(Woodsman(H), Serf(L)),
-                1 => career!(Woodsman(H), Serf(L)),
-                2 => career!(Fisher(M), Mootman(F)),
-                3 => career!(Fisher(M), Mootman(F)),
-                4 => career!(Herder(T), Smallholder(F)),
-                5 => career!(Herder(T), Smallholder(F)),
-                6..=9 => career!(Join Gentry),
-                _ => unreachable!(),
-            },
-            Performers => match rank {
-                0 => career!(Üstaler(C)),
-                1 => career!(Üstaler(C), Skald(E)),
-                2 => career!(Lundstaler(C), Skald(E)),
-                3 => career!(Lundstaler(P), Runescribe(A)),
-                4 => career!(Speaker(Z), Runescribe(A)),
-                5 => career!(Speaker(Z), Chronicler(S)),
-                6..=9 => career!(Remain at rank 5),
-                _ => unreachable!(),
-            },
-            Plebeians | Army | Gentry => match rank {
-                0..=1 => career!(Join Vagabonds),
-                2 => career!(Drabling(G), Sailor(M)),
-                3 => career!(Clan Crafter(A), Sailor(M)),
-                4 => career!(Clan Crafter(A), Shield Bearer(I)),
-                5 => career!(Herder(T), Shield Bearer(I)),
-                6 => career!(Herder(T), Thane(K)),
-                7 => career!(Courtier(O), Thane(K)),
-                8 => career!(Jarl(N)),
-                9 => career!(Remain at rank 8),
-                _ => unreachable!(),
-            },
-            Watch => match rank {
-                0..=1 => career!(Join Scholars),
-                2 => career!(Inquisitor(I)),
-                3 => career!(Inquisitor(I)),
-                4 => career!(Inquisition Seer(D), Bluecoat(H)),
-                5 => career!(Inquisition Seer(D), Bluecoat Seer(S)),
-                6 => career!(Sceptre Scribe(O), Temple Guardian(K)),
-                7 => career!(Sceptre Scribe(O), Temple Guardian(K)),
-                8..=9 => career!(Remain at rank 7),
-                _ => unreachable!(),
-            },
-            Scholars => match rank {
-                0 => career!(Reroll affiliation with disadvantage),
-                1 => career!(Mendicant(B)),
-                2 => career!(Mendicant(B), Apothecary(P)),
-                3 => career!(Cleric(Z), Apothecary(P)),
-                4 => career!(Cleric(Z), Temple Seer(S)),
-                5 => career!(Cleric(Z), Temple Seer(S)),
-                6 => career!(Sceptre Scribe(O)),
-                7 => career!(Sceptre Scribe(O)),
-                8..=9 => career!(Remain at rank 7),
-                _ => unreachable!(),
-            },
-        },
-    }
+/// Every table, affiliation and rank that leads to a career, or a class of career
+pub fn career_origins(target: impl Into<Target>) -> Origins {
+    origins(bundled(), target)
 }
 
 impl Affiliation {
     pub fn star(&self, location: &Location) -> CareerTableStar {
-        match location.career_table {
-            CareerTable::ValiantEmpire => match self {
-                Watch | Scholars => CareerTableStar::NeedsFaith(Faith::Accorder),
-                _ => CareerTableStar::None,
-            },
-            CareerTable::Nivena => match self {
-                Performers | Plebeians | Scholars | Gentry => {
-                    CareerTableStar::NeedsFaith(Faith::IdealLodges)
-                }
-                _ => CareerTableStar::None,
-            },
-            CareerTable::Marolaw => match self {
-                Performers => CareerTableStar::NeedsFaith(Faith::OrodTast),
-                Watch | Scholars => CareerTableStar::NeedsFaith(Faith::Accorder),
-                _ => CareerTableStar::None,
-            },
-        }
+        bundled().star(location.career_table, *self)
     }
 }
@@ -0,0 +1,383 @@
+// The career tables are big hand-written matches, and one typo in a redirect is enough to send an
+// event round in circles or off the edge of a table. This walks every table, affiliation and
+// rank, following every redirect the way `change_rank` would, and reports anything that can't
+// end in a career someone is able to take.
+use crate::ChargenError;
+use crate::data::careers::{AFFILIATIONS, Affiliation, Career, CareerTableEntry, CareerTableStar};
+use crate::data::locations::{CAREER_TABLES, CareerTable, Culture, DEMOGRAPHICS, Demographic};
+use crate::data::tables::Tables;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+
+const RANKS: std::ops::RangeInclusive<i8> = 0..=9;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Finding {
+    /// A d100 on an affiliation table doesn't land on any affiliation
+    AffiliationOffTable {
+        table: CareerTable,
+        demographic: Demographic,
+        error: ChargenError,
+    },
+    /// A rank die can give a rank outside 0-9
+    RankOffTable {
+        table: CareerTable,
+        affiliation: Affiliation,
+        d6: i8,
+        rank: i8,
+    },
+    /// An affiliation has no entry at some rank, either directly or at the end of a redirect
+    CareerOffTable {
+        table: CareerTable,
+        affiliation: Affiliation,
+        rank: i8,
+        error: ChargenError,
+    },
+    /// Following the redirects from the first step comes back around without reaching a career
+    Cycle {
+        table: CareerTable,
+        path: Vec<(Affiliation, i8)>,
+    },
+    /// Every affiliation at this rank ends up rerolling, so a reroll can never settle
+    EndlessReroll { table: CareerTable, rank: i8 },
+    /// Every option here needs a particular culture, so anyone else is left with no career
+    NoCareerFor {
+        table: CareerTable,
+        affiliation: Affiliation,
+        rank: i8,
+        cultures: Vec<Culture>,
+    },
+    /// A roll on a table lands on no rows, or more than one
+    Coverage {
+        table: String,
+        roll: i8,
+        rows: usize,
+    },
+    /// Two careers or birthplaces have the same id
+    DuplicateId { kind: &'static str, id: String },
+    /// A place that isn't far afield needs a demographic
+    NoDemographic { place: String },
+    /// A place uses a career table that doesn't exist
+    MissingCareerTable { table: CareerTable },
+    /// A career table doesn't say what affiliations a demographic rolls
+    MissingAffiliationRoll {
+        table: CareerTable,
+        demographic: Demographic,
+    },
+    /// A career table is missing an affiliation
+    MissingAffiliation {
+        table: CareerTable,
+        affiliation: Affiliation,
+    },
+    /// A career is listed, but nothing in chargen can ever lead to it
+    Unreachable {
+        table: CareerTable,
+        affiliation: Affiliation,
+        rank: i8,
+        career: Career,
+    },
+}
+
+impl Finding {
+    /// Whether this breaks chargen, rather than just being worth knowing
+    pub fn is_error(&self) -> bool {
+        !matches!(self, Finding::Unreachable { .. })
+    }
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Finding::Coverage { table, roll, rows } => {
+                write!(
+                    f,
+                    "a roll of {roll} on the {table} table lands on {rows} rows"
+                )
+            }
+            Finding::DuplicateId { kind, id } => write!(f, "more than one {kind} has the id {id}"),
+            Finding::NoDemographic { place } => write!(f, "{place} has no demographic"),
+            Finding::MissingCareerTable { table } => write!(f, "there's no {table:?} table"),
+            Finding::MissingAffiliationRoll { table, demographic } => write!(
+                f,
+                "the {table:?} table has no affiliations for {demographic:?} places"
+            ),
+            Finding::MissingAffiliation { table, affiliation } => {
+                write!(f, "the {table:?} table has no {affiliation}")
+            }
+            Finding::AffiliationOffTable {
+                table,
+                demographic,
+                error,
+            } => write!(f, "{table:?} {demographic:?} affiliations: {error}"),
+            Finding::RankOffTable {
+                table,
+                affiliation,
+                d6,
+                rank,
+            } => write!(
+                f,
+                "{table:?} {affiliation}: a rank die of {d6} gives rank {rank}"
+            ),
+            Finding::CareerOffTable {
+                table,
+                affiliation,
+                rank,
+                error,
+            } => write!(f, "{table:?} {affiliation} at rank {rank}: {error}"),
+            Finding::Cycle { table, path } => {
+                write!(f, "{table:?} redirects go round in circles:")?;
+                for (affiliation, rank) in path {
+                    write!(f, " {affiliation} {rank} ->")?;
+                }
+                write!(f, " {} {}", path[0].0, path[0].1)
+            }
+            Finding::EndlessReroll { table, rank } => write!(
+                f,
+                "{table:?} at rank {rank}: every affiliation rerolls, so the reroll never ends"
+            ),
+            Finding::NoCareerFor {
+                table,
+                affiliation,
+                rank,
+                cultures,
+            } => write!(
+                f,
+                "{table:?} {affiliation} at rank {rank}: only {cultures:?} can take any career"
+            ),
+            Finding::Unreachable {
+                table,
+                affiliation,
+                rank,
+                career,
+            } => write!(
+                f,
+                "{table:?} {affiliation} at rank {rank}: {career} can never be reached"
+            ),
+        }
+    }
+}
+
+// where following an entry's redirects ends up
+enum Resolved {
+    Careers {
+        affiliation: Affiliation,
+        rank: i8,
+        options: Vec<(Career, CareerTableStar)>,
+    },
+    Reroll,
+}
+
+fn resolve(
+    tables: &Tables,
+    table: CareerTable,
+    mut affiliation: Affiliation,
+    mut rank: i8,
+) -> Result<Resolved, Finding> {
+    let mut path = vec![];
+    loop {
+        if let Some(start) = path.iter().position(|&step| step == (affiliation, rank)) {
+            // only report the loop itself, not however we wandered into it
+            path.drain(..start);
+            return Err(Finding::Cycle { table, path });
+        }
+        path.push((affiliation, rank));
+
+        let entry =
+            tables
+                .careers(table, affiliation, rank)
+                .map_err(|error| Finding::CareerOffTable {
+                    table,
+                    affiliation,
+                    rank,
+                    error,
+                })?;
+        match entry {
+            CareerTableEntry::Career(career, star) => {
+                return Ok(Resolved::Careers {
+                    affiliation,
+                    rank,
+                    options: vec![(career, star)],
+                });
+            }
+            CareerTableEntry::Careers(c1, c2) => {
+                return Ok(Resolved::Careers {
+                    affiliation,
+                    rank,
+                    options: vec![c1, c2],
+                });
+            }
+            CareerTableEntry::RemainAtRank(r) => rank = r,
+            CareerTableEntry::ChangeAffiliation(a) => affiliation = a,
+            CareerTableEntry::RerollWithDisadvantage | CareerTableEntry::Reroll => {
+                return Ok(Resolved::Reroll);
+            }
+        }
+    }
+}
+
+fn lint_table(tables: &Tables, table: CareerTable, findings: &mut Vec<Finding>) {
+    // the affiliations a location using this table can roll
+    let mut rollable = vec![];
+    for demographic in DEMOGRAPHICS {
+        for roll in 1..=100 {
+            match tables.affiliation(table, demographic, roll) {
+                Ok(affiliation) if !rollable.contains(&affiliation) => rollable.push(affiliation),
+                Ok(_) => {}
+                Err(error) => {
+                    findings.push(Finding::AffiliationOffTable {
+                        table,
+                        demographic,
+                        error,
+                    });
+                    break;
+                }
+            }
+        }
+    }
+
+    // guardians start at their affiliation's rank die. Every omen then either keeps their rank or
+    // drops it and rerolls affiliation, so a character can start at any affiliation and any rank
+    // up to the highest a guardian can have
+    let mut starts = vec![];
+    let mut highest = 0;
+    for &affiliation in &rollable {
+        for d6 in 1..=6 {
+            // a missing affiliation gets reported when the tables are validated
+            let Ok(rank) = tables.rank(table, affiliation, d6) else {
+                continue;
+            };
+            if !RANKS.contains(&rank) {
+                findings.push(Finding::RankOffTable {
+                    table,
+                    affiliation,
+                    d6,
+                    rank,
+                });
+            }
+            let rank = rank.clamp(0, 9);
+            highest = highest.max(rank);
+            starts.push((affiliation, rank));
+        }
+    }
+    for &affiliation in &rollable {
+        for rank in 0..=highest {
+            starts.push((affiliation, rank));
+        }
+    }
+
+    // follow each start to where it settles, rerolls going back to any affiliation at that rank
+    let mut seen = HashSet::new();
+    let mut landed = HashSet::new();
+    while let Some((affiliation, rank)) = starts.pop() {
+        if !seen.insert((affiliation, rank)) {
+            continue;
+        }
+        match resolve(tables, table, affiliation, rank) {
+            Ok(Resolved::Careers {
+                affiliation, rank, ..
+            }) => {
+                landed.insert((affiliation, rank));
+            }
+            Ok(Resolved::Reroll) => starts.extend(rollable.iter().map(|&a| (a, rank))),
+            // reported below, when the whole table is walked
+            Err(_) => {}
+        }
+    }
+    let reachable: HashSet<Career> = landed
+        .iter()
+        .filter_map(|&(affiliation, rank)| tables.careers(table, affiliation, rank).ok())
+        .flat_map(|entry| match entry {
+            CareerTableEntry::Career(career, _) => vec![career],
+            CareerTableEntry::Careers((c1, _), (c2, _)) => vec![c1, c2],
+            _ => vec![],
+        })
+        .collect();
+
+    for rank in RANKS {
+        let mut rerolls = true;
+        for affiliation in AFFILIATIONS {
+            let options = match resolve(tables, table, affiliation, rank) {
+                Ok(Resolved::Careers { options, .. }) => options,
+                Ok(Resolved::Reroll) => continue,
+                Err(finding) => {
+                    if !findings.contains(&finding) {
+                        findings.push(finding);
+                    }
+                    continue;
+                }
+            };
+            if rollable.contains(&affiliation) {
+                rerolls = false;
+            }
+
+            // only check the entries actually written at this rank, not where they redirect to,
+            // so each gets reported once
+            if !matches!(
+                tables.careers(table, affiliation, rank),
+                Ok(CareerTableEntry::Career(..) | CareerTableEntry::Careers(..))
+            ) {
+                continue;
+            }
+            let cultures: Vec<Culture> = options
+                .iter()
+                .map(|&(_, star)| match star {
+                    CareerTableStar::NeedsFaithAndCulture(_, culture) => Some(culture),
+                    _ => None,
+                })
+                .collect::<Option<_>>()
+                .unwrap_or_default();
+            if !cultures.is_empty() {
+                findings.push(Finding::NoCareerFor {
+                    table,
+                    affiliation,
+                    rank,
+                    cultures,
+                });
+            }
+            for (career, _) in options {
+                if !reachable.contains(&career) {
+                    findings.push(Finding::Unreachable {
+                        table,
+                        affiliation,
+                        rank,
+                        career,
+                    });
+                }
+            }
+        }
+        if rerolls {
+            findings.push(Finding::EndlessReroll { table, rank });
+        }
+    }
+}
+
+/// Follows every route through the career tables, returning everywhere they go wrong
+pub fn lint(tables: &Tables) -> Vec<Finding> {
+    let mut findings = vec![];
+    for table in CAREER_TABLES {
+        if tables.career_tables.contains_key(&table) {
+            lint_table(tables, table, &mut findings);
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::tables::bundled;
+
+    #[test]
+    fn test_tables() {
+        // nobody can start chargen above rank 7 in the empire, so the top of the table is out of reach
+        assert_eq!(
+            lint(bundled()),
+            [Finding::Unreachable {
+                table: CareerTable::ValiantEmpire,
+                affiliation: Affiliation::Gentry,
+                rank: 9,
+                career: Career::from_id("liege_lord").unwrap(),
+            }]
+        );
+    }
+}
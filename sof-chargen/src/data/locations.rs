@@ -1,5 +1,9 @@
+use crate::ChargenError;
 use crate::data::locations::Demographic::{Border, Rural, Urban};
+use crate::data::roll_table::{Die, Nested, RollTable};
+use crate::data::tables::bundled;
 use std::fmt::{Display, Formatter};
+use std::sync::LazyLock;
 
 // todo there's not a 1-1 correspondence from culture to language - should those be explicit?
 // the document seems to suggest there should be... ask lys
@@ -65,22 +69,34 @@ impl Display for Faith {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
 pub enum Demographic {
     Urban,
     Rural,
     Border,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub const DEMOGRAPHICS: [Demographic; 3] = [Urban, Rural, Border];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
 pub enum CareerTable {
     ValiantEmpire,
     Nivena,
     Marolaw,
 }
 
+pub const CAREER_TABLES: [CareerTable; 3] = [
+    CareerTable::ValiantEmpire,
+    CareerTable::Nivena,
+    CareerTable::Marolaw,
+];
+
+/// A birthplace, as rolled. Saved as just its id in birthplaces.ron and the demographic it
+/// rolled; everything else is looked up again on load.
 #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(try_from = "LocationRef", into = "LocationRef")]
 pub struct Location {
+    pub id: String,
     pub name: String,
     pub culture: Culture,
     pub secondary_culture: Culture,
@@ -91,271 +107,75 @@ pub struct Location {
     pub far_afield: bool,
 }
 
-pub fn location_table(d6s: (i8, i8, i8), d3: i8) -> Location {
-    let culture;
-    let faith;
-    let secondary_faith;
-    let name;
-    let secondary_culture;
-    let demographic;
-    let career_table;
-    let far_afield;
+#[derive(serde::Deserialize, serde::Serialize)]
+struct LocationRef {
+    id: String,
+    demographic: Demographic,
+}
 
-    match d6s.0 {
-        1 | 2 => {
-            // Valiant empire
-            career_table = CareerTable::ValiantEmpire;
-            culture = Culture::Varlish;
-            faith = Faith::Accorder;
-            secondary_faith = Faith::IdealLodges;
-            far_afield = false;
-            match d6s.1 {
-                1 => {
-                    name = "The Imperial Capital of Duruza";
-                    secondary_culture = Culture::Kremish;
-                    demographic = Demographic::Urban;
-                }
-                2 => {
-                    name = "Luruna, the North Sentinel";
-                    secondary_culture = Culture::Kremish;
-                    demographic = Demographic::Urban;
-                }
-                3 => {
-                    name = "The volcanic farmlands around Caldis";
-                    secondary_culture = Culture::Kremish;
-                    demographic = Demographic::Rural;
-                }
-                4 => {
-                    name = "The treacherous Mervaal marshes";
-                    secondary_culture = Culture::Kremish;
-                    demographic = Demographic::Rural;
-                }
-                5 => {
-                    name = "Along the dusty Bone Road to Orvaal";
-                    secondary_culture = Culture::Revic;
-                    demographic = Demographic::Border;
-                }
-                6 => {
-                    name = "In the shadow of Mount Cothornis";
-                    secondary_culture = Culture::Revic;
-                    demographic = Demographic::Border;
-                }
-                _ => unreachable!(),
-            }
-        }
-        3 => {
-            // Nivena
-            career_table = CareerTable::Nivena;
-            culture = Culture::Varlish;
-            faith = Faith::IdealLodges;
-            far_afield = false;
-            match d6s.1 {
-                1 => {
-                    name = "Niiva, City of Intrigues";
-                    secondary_culture = Culture::Kremish;
-                    secondary_faith = Faith::Gytungrug;
-                    demographic = Demographic::Urban;
-                }
-                2 => {
-                    name = "The Fortified Port of Murga";
-                    secondary_culture = Culture::Revic;
-                    secondary_faith = Faith::OrodTast;
-                    demographic = Demographic::Urban;
-                }
-                3 => {
-                    name = "The riverlands around Faraxi";
-                    secondary_culture = Culture::Kremish;
-                    secondary_faith = Faith::Gytungrug;
-                    demographic = Demographic::Rural;
-                }
-                4 => {
-                    name = "Around the Chalys Pinewoods";
-                    secondary_culture = Culture::Kremish;
-                    secondary_faith = Faith::Gytungrug;
-                    demographic = Demographic::Rural;
-                }
-                5 => {
-                    name = "In the barren, hilly Sorrow";
-                    secondary_culture = Culture::Kremish;
-                    secondary_faith = Faith::Accorder;
-                    demographic = Demographic::Border;
-                }
-                6 => {
-                    name = "A colony on an island or by Suruso";
-                    secondary_culture = Culture::Revic;
-                    secondary_faith = Faith::OrodTast;
-                    demographic = Demographic::Border;
-                }
-                _ => unreachable!(),
-            }
-        }
-        4 | 5 => {
-            // Marolaw
-            career_table = CareerTable::Marolaw;
-            culture = Culture::Revic;
-            secondary_culture = Culture::Varlish;
-            faith = Faith::Accorder;
-            far_afield = false;
-            match d6s.1 {
-                1 => {
-                    name = "The Administrative Capital at Marodell";
-                    secondary_faith = Faith::OrodTast;
-                    demographic = Demographic::Urban;
-                }
-                2 => {
-                    name = "Varnoss, the Bloody Bulwark";
-                    secondary_faith = Faith::OrodTast;
-                    demographic = Demographic::Urban;
-                }
-                3 => {
-                    name = "Stravarn and the lush southeast coast";
-                    secondary_faith = Faith::OrodTast;
-                    demographic = Demographic::Rural;
-                }
-                4 => {
-                    name = "The rugged woodland of the Harsten Vale";
-                    secondary_faith = Faith::OrodTast;
-                    demographic = Demographic::Rural;
-                }
-                5 => {
-                    name = "The sun-bleached Dry Coast";
-                    secondary_faith = Faith::IdealLodges;
-                    demographic = Demographic::Border;
-                }
-                6 => {
-                    name = "The wood and marshland of the Rimelight";
-                    secondary_faith = Faith::IdealLodges;
-                    demographic = Demographic::Border;
-                }
-                _ => unreachable!(),
-            }
-        }
-        6 => {
-            // Somewhere further afield
-            far_afield = true;
-            match d6s.1 {
-                1 => {
-                    name = "The Fallen Kingdom of  Kr’meche";
-                    culture = Culture::Kremish;
-                    faith = Faith::Gytungrug;
-                    career_table = CareerTable::ValiantEmpire;
-                }
-                2 => {
-                    name = "Gollg’rym, the frost-bitten wastes";
-                    culture = Culture::Kremish;
-                    faith = Faith::Gytungrug;
-                    career_table = CareerTable::Marolaw;
-                }
-                3 => {
-                    name = "The Ash Mountains, or the Sea of Shades";
-                    culture = Culture::Revic;
-                    faith = Faith::OrodTast;
-                    career_table = CareerTable::Marolaw;
-                }
-                4 => {
-                    name = "The isles of the Carmine Sea Compact";
-                    culture = Culture::Myrsc(Faith::KeystonePantheon);
-                    faith = Faith::KeystonePantheon;
-                    career_table = CareerTable::Nivena;
-                }
-                5 => {
-                    name = "The bustling Thousand Spires Coast";
-                    culture = Culture::Clovienne;
-                    faith = Faith::Accorder;
-                    career_table = CareerTable::ValiantEmpire;
-                }
-                6 => {
-                    // Even Further
-                    match d6s.2 {
-                        1 => {
-                            name = "The hidebound, riven Rose Coast";
-                            culture = Culture::Grevolin;
-                            faith = Faith::Grevite;
-                            career_table = CareerTable::Nivena;
-                        }
-                        2 => {
-                            name = "The plague-blighted Crown Coast";
-                            culture = Culture::Grevolin;
-                            faith = Faith::Flametouched;
-                            career_table = CareerTable::Nivena;
-                        }
-                        3 => {
-                            name = "A city clinging to the Umbral Coast";
-                            culture = Culture::Myrsc(Faith::AmberheartCult);
-                            faith = Faith::AmberheartCult;
-                            career_table = CareerTable::Nivena;
-                        }
-                        4 => {
-                            name = "The blasted dunes of the Great Flat";
-                            culture = Culture::Shander;
-                            faith = Faith::Accorder;
-                            career_table = CareerTable::Marolaw;
-                        }
-                        5 => {
-                            name = "Neminyah, the rotting heart";
-                            culture = Culture::Myrsc(Faith::UnseenHand);
-                            faith = Faith::UnseenHand;
-                            career_table = CareerTable::Marolaw;
-                        }
-                        6 => {
-                            name = "The brackish jungles of Rezankath";
-                            culture = Culture::Torienne;
-                            faith = Faith::TempleOfSeraf;
-                            career_table = CareerTable::ValiantEmpire;
-                        }
-                        _ => unreachable!(),
-                    }
-                }
-                _ => unreachable!(),
-            }
-            demographic = match d3 {
-                1 => Urban,
-                2 => Rural,
-                3 => Border,
-                _ => unreachable!(),
-            };
-            // these locations never use the secondary faith/culture mechanic and don't list any
-            secondary_faith = faith;
-            secondary_culture = culture;
-        }
-        _ => unreachable!(),
-    }
-    Location {
-        name: name.to_owned(),
-        culture,
-        secondary_culture,
-        faith,
-        secondary_faith,
-        demographic,
-        career_table,
-        far_afield,
+impl TryFrom<LocationRef> for Location {
+    type Error = String;
+
+    fn try_from(LocationRef { id, demographic }: LocationRef) -> Result<Self, Self::Error> {
+        bundled()
+            .location_by_id(&id, demographic)
+            .ok_or_else(|| format!("there's no birthplace with the id \"{id}\""))
     }
 }
 
-// pulled out due to being used in multiple places
-pub fn further_afield_culture(d6s: (i8, i8)) -> Culture {
-    match d6s.0 {
-        1 => Culture::Kremish,
-        2 => Culture::Kremish,
-        3 => Culture::Revic,
-        4 => Culture::Myrsc(Faith::KeystonePantheon),
-        5 => Culture::Clovienne,
-        6 => {
-            // Even Further
-            match d6s.1 {
-                1 => Culture::Grevolin,
-                2 => Culture::Grevolin,
-                3 => Culture::Myrsc(Faith::AmberheartCult),
-                4 => Culture::Shander,
-                5 => Culture::Myrsc(Faith::AmberheartCult),
-                6 => Culture::Torienne,
-                _ => unreachable!(),
-            }
+impl From<Location> for LocationRef {
+    fn from(location: Location) -> Self {
+        LocationRef {
+            id: location.id,
+            demographic: location.demographic,
         }
-        _ => unreachable!(),
     }
 }
 
+// the table itself lives in birthplaces.ron, see data::tables
+pub fn location_table(d6s: (i8, i8, i8), d3: i8) -> Result<Location, ChargenError> {
+    bundled().location(d6s, d3)
+}
+
+static FURTHER_AFIELD_CULTURE: LazyLock<RollTable<Nested<Culture>>> = LazyLock::new(|| {
+    use Nested::{Entry, RollAgain};
+    RollTable::new(
+        Die::D6,
+        [
+            ((1, 2), Entry(Culture::Kremish)),
+            ((3, 3), Entry(Culture::Revic)),
+            ((4, 4), Entry(Culture::Myrsc(Faith::KeystonePantheon))),
+            ((5, 5), Entry(Culture::Clovienne)),
+            // Even Further
+            (
+                (6, 6),
+                RollAgain(RollTable::new(
+                    Die::D6,
+                    [
+                        ((1, 2), Entry(Culture::Grevolin)),
+                        ((3, 3), Entry(Culture::Myrsc(Faith::AmberheartCult))),
+                        ((4, 4), Entry(Culture::Shander)),
+                        ((5, 5), Entry(Culture::Myrsc(Faith::AmberheartCult))),
+                        ((6, 6), Entry(Culture::Torienne)),
+                    ],
+                )),
+            ),
+        ],
+    )
+});
+
+/// The cultures of places beyond the known world
+pub fn further_afield_culture_table() -> &'static RollTable<Nested<Culture>> {
+    &FURTHER_AFIELD_CULTURE
+}
+
+// pulled out due to being used in multiple places
+pub fn further_afield_culture(d6s: (i8, i8)) -> Result<Culture, ChargenError> {
+    FURTHER_AFIELD_CULTURE
+        .get_nested("further afield culture", &[d6s.0, d6s.1])
+        .copied()
+}
+
 pub fn associated_faith(culture: Culture) -> Faith {
     match culture {
         Culture::Varlish => Faith::Accorder,
@@ -369,46 +189,134 @@ pub fn associated_faith(culture: Culture) -> Faith {
     }
 }
 
-pub fn get_culture(location: &Location, d6s: (i8, i8, i8, i8)) -> Culture {
-    if location.far_afield {
-        return location.culture;
-    }
+/// What a roll on the culture table gives, before knowing where you were born
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CultureRoll {
+    Primary,
+    Secondary,
+    Culture(Culture),
+}
 
-    match d6s.0 {
-        1..=3 => location.culture,
-        4 | 5 => location.secondary_culture,
-        6 => match d6s.1 {
-            1..=3 => Culture::Varlish,
-            4 => Culture::Revic,
-            5 => Culture::Kremish,
-            6 => further_afield_culture((d6s.2, d6s.3)),
-            _ => unreachable!(),
-        },
-        _ => unreachable!(),
+impl Display for CultureRoll {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CultureRoll::Primary => write!(f, "your birthplace's culture"),
+            CultureRoll::Secondary => write!(f, "your birthplace's secondary culture"),
+            CultureRoll::Culture(culture) => write!(f, "{culture}"),
+        }
     }
 }
 
-pub fn get_faith(location: &Location, culture: Culture, d6s: (i8, i8)) -> Faith {
+static CULTURE: LazyLock<RollTable<Nested<CultureRoll>>> = LazyLock::new(|| {
+    use Nested::{Entry, RollAgain};
+    RollTable::new(
+        Die::D6,
+        [
+            ((1, 3), Entry(CultureRoll::Primary)),
+            ((4, 5), Entry(CultureRoll::Secondary)),
+            (
+                (6, 6),
+                RollAgain(RollTable::new(
+                    Die::D6,
+                    [
+                        ((1, 3), Entry(CultureRoll::Culture(Culture::Varlish))),
+                        ((4, 4), Entry(CultureRoll::Culture(Culture::Revic))),
+                        ((5, 5), Entry(CultureRoll::Culture(Culture::Kremish))),
+                        (
+                            (6, 6),
+                            RollAgain(
+                                FURTHER_AFIELD_CULTURE.map_nested(&|&c| CultureRoll::Culture(c)),
+                            ),
+                        ),
+                    ],
+                )),
+            ),
+        ],
+    )
+});
+
+/// The culture table for anyone not born far afield
+pub fn culture_table() -> &'static RollTable<Nested<CultureRoll>> {
+    &CULTURE
+}
+
+/// Rolls `d6` only as many times as the culture table needs, and not at all if born far afield
+pub fn get_culture(location: &Location, d6: impl FnMut() -> i8) -> Result<Culture, ChargenError> {
     if location.far_afield {
-        return location.faith;
+        return Ok(location.culture);
     }
 
-    match d6s.0 {
-        1 | 2 => {
-            if culture == Culture::Kremish {
-                Faith::Gytungrug
-            } else {
-                location.faith
-            }
+    Ok(match CULTURE.get_rolling("culture", d6)? {
+        CultureRoll::Primary => location.culture,
+        CultureRoll::Secondary => location.secondary_culture,
+        CultureRoll::Culture(culture) => *culture,
+    })
+}
+
+/// What a roll on the faith table gives, before knowing where you were born or your culture
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FaithRoll {
+    // the kremish keep to Gytungrug wherever they are
+    Primary,
+    Secondary,
+    Associated,
+    Faith(Faith),
+}
+
+impl Display for FaithRoll {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FaithRoll::Primary => write!(f, "your birthplace's faith (Gytungrug if Kremish)"),
+            FaithRoll::Secondary => write!(f, "your birthplace's secondary faith"),
+            FaithRoll::Associated => write!(f, "your culture's faith"),
+            FaithRoll::Faith(faith) => write!(f, "{faith}"),
         }
-        3 => location.secondary_faith,
-        4 | 5 => associated_faith(culture),
-        6 => match d6s.1 {
-            1..=3 => Faith::Accorder,
-            4 | 5 => Faith::Irreligious,
-            6 => Faith::TempleOfSeraf,
-            _ => unreachable!(),
-        },
-        _ => unreachable!(),
     }
 }
+
+static FAITH: LazyLock<RollTable<Nested<FaithRoll>>> = LazyLock::new(|| {
+    use Nested::{Entry, RollAgain};
+    RollTable::new(
+        Die::D6,
+        [
+            ((1, 2), Entry(FaithRoll::Primary)),
+            ((3, 3), Entry(FaithRoll::Secondary)),
+            ((4, 5), Entry(FaithRoll::Associated)),
+            (
+                (6, 6),
+                RollAgain(RollTable::new(
+                    Die::D6,
+                    [
+                        ((1, 3), Entry(FaithRoll::Faith(Faith::Accorder))),
+                        ((4, 5), Entry(FaithRoll::Faith(Faith::Irreligious))),
+                        ((6, 6), Entry(FaithRoll::Faith(Faith::TempleOfSeraf))),
+                    ],
+                )),
+            ),
+        ],
+    )
+});
+
+/// The faith table for anyone not born far afield
+pub fn faith_table() -> &'static RollTable<Nested<FaithRoll>> {
+    &FAITH
+}
+
+/// Rolls `d6` only as many times as the faith table needs, and not at all if born far afield
+pub fn get_faith(
+    location: &Location,
+    culture: Culture,
+    d6: impl FnMut() -> i8,
+) -> Result<Faith, ChargenError> {
+    if location.far_afield {
+        return Ok(location.faith);
+    }
+
+    Ok(match FAITH.get_rolling("faith", d6)? {
+        FaithRoll::Primary if culture == Culture::Kremish => Faith::Gytungrug,
+        FaithRoll::Primary => location.faith,
+        FaithRoll::Secondary => location.secondary_faith,
+        FaithRoll::Associated => associated_faith(culture),
+        FaithRoll::Faith(faith) => *faith,
+    })
+}
@@ -0,0 +1,381 @@
+// Exact odds for where a birthplace puts your guardians: the affiliation they roll, the rank die
+// under it, and the careers they end up choosing between. Every face of every die is counted
+// rather than sampled. The one wrinkle is rerolls, which can land on another reroll - those are
+// solved as a small linear system instead of being followed forever.
+use crate::ChargenError;
+use crate::data::careers::{Affiliation, Career, CareerTableEntry, CareerTableStar};
+use crate::data::locations::{Culture, Location};
+use crate::data::tables::Tables;
+
+/// Where a roll leaves someone: their affiliation and rank once every redirect has been followed,
+/// and the careers they can pick from there
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Standing {
+    pub affiliation: Affiliation,
+    pub rank: i8,
+    /// Only the careers open to the culture asked about
+    pub options: Vec<(Career, CareerTableStar)>,
+}
+
+/// The chance of each outcome of rolling for your guardians' standing
+#[derive(Clone, Debug)]
+pub struct Odds {
+    /// The affiliation first rolled, before any redirects
+    pub rolled: Vec<(Affiliation, f64)>,
+    /// The rank first rolled, on that affiliation's rank die
+    pub ranks: Vec<(i8, f64)>,
+    /// Where everyone ends up, most likely first
+    pub standings: Vec<(Standing, f64)>,
+}
+
+impl Odds {
+    /// The chance of first rolling this affiliation
+    pub fn affiliation(&self, affiliation: Affiliation) -> f64 {
+        sum_where(&self.rolled, |&a| a == affiliation)
+    }
+
+    /// The chance of first rolling this rank
+    pub fn rank(&self, rank: i8) -> f64 {
+        sum_where(&self.ranks, |&r| r == rank)
+    }
+
+    /// The chance of this career being on offer
+    pub fn career(&self, career: Career) -> f64 {
+        sum_where(&self.standings, |standing| {
+            standing.options.iter().any(|&(c, _)| c == career)
+        })
+    }
+}
+
+fn sum_where<T>(odds: &[(T, f64)], f: impl Fn(&T) -> bool) -> f64 {
+    odds.iter().filter(|(t, _)| f(t)).map(|(_, p)| p).sum()
+}
+
+fn add<T: PartialEq>(odds: &mut Vec<(T, f64)>, outcome: T, p: f64) {
+    match odds.iter_mut().find(|(t, _)| *t == outcome) {
+        Some((_, total)) => *total += p,
+        None => odds.push((outcome, p)),
+    }
+}
+
+/// The chance of each result on `kh(dice d 100)`, indexed by result (0 is never rolled).
+/// Each tens die shares the one units die, so this counts every combination of them.
+fn d100_odds(dice: usize) -> [f64; 101] {
+    let mut odds = [0.0; 101];
+    let combinations = 10usize.pow(dice as u32 + 1);
+    for combination in 0..combinations {
+        let units = (combination % 10) as i8;
+        let mut rest = combination / 10;
+        let mut highest = 0;
+        for _ in 0..dice {
+            let tens = (rest % 10) as i8 * 10;
+            rest /= 10;
+            let result = if tens == 0 && units == 0 {
+                100
+            } else {
+                tens + units
+            };
+            highest = highest.max(result);
+        }
+        odds[highest as usize] += 1.0 / combinations as f64;
+    }
+    odds
+}
+
+// where following an entry's redirects ends up
+enum Resolved {
+    Standing(Standing),
+    // rerolling affiliation at this rank, with this much extra disadvantage
+    Reroll(i8, usize),
+}
+
+fn resolve(
+    tables: &Tables,
+    location: &Location,
+    culture: Culture,
+    mut affiliation: Affiliation,
+    mut rank: i8,
+) -> Result<Resolved, ChargenError> {
+    let table = location.career_table;
+    let mut seen = vec![];
+    loop {
+        if seen.contains(&(affiliation, rank)) {
+            return Err(ChargenError::BadTableEntry(format!(
+                "the {table:?} redirects from {affiliation} at rank {rank} go round in circles"
+            )));
+        }
+        seen.push((affiliation, rank));
+        let options = match tables.careers(table, affiliation, rank)? {
+            CareerTableEntry::Career(career, star) => vec![(career, star)],
+            CareerTableEntry::Careers(c1, c2) => vec![c1, c2],
+            CareerTableEntry::RemainAtRank(r) => {
+                rank = r;
+                continue;
+            }
+            CareerTableEntry::ChangeAffiliation(a) => {
+                affiliation = a;
+                continue;
+            }
+            CareerTableEntry::Reroll => return Ok(Resolved::Reroll(rank, 0)),
+            CareerTableEntry::RerollWithDisadvantage => return Ok(Resolved::Reroll(rank, 1)),
+        };
+        let options = options
+            .into_iter()
+            .filter(|&(_, star)| match star {
+                CareerTableStar::NeedsFaithAndCulture(_, c) => c == culture,
+                _ => true,
+            })
+            .collect();
+        return Ok(Resolved::Standing(Standing {
+            affiliation,
+            rank,
+            options,
+        }));
+    }
+}
+
+/// The odds of every affiliation, rank and set of careers for guardians of `culture` born in
+/// `location`. Where a table says to reroll affiliation, they're assumed to convert to whatever
+/// faith the new affiliation needs rather than dropping a rank, as they do on their first roll.
+pub fn odds(tables: &Tables, location: &Location, culture: Culture) -> Result<Odds, ChargenError> {
+    // guardians outside the dominant culture roll with disadvantage
+    let base = if location.culture == culture { 1 } else { 2 };
+    let affiliation_odds = |disadvantage: usize| -> Result<Vec<(Affiliation, f64)>, ChargenError> {
+        let mut odds = vec![];
+        for (roll, p) in d100_odds(base + disadvantage).into_iter().enumerate() {
+            if p > 0.0 {
+                let affiliation =
+                    tables.affiliation(location.career_table, location.demographic, roll as i8)?;
+                add(&mut odds, affiliation, p);
+            }
+        }
+        Ok(odds)
+    };
+
+    let rolled = affiliation_odds(0)?;
+    let mut ranks = vec![];
+    // where the first roll sends everyone, before following any rerolls
+    let mut first = vec![];
+    for &(affiliation, p) in &rolled {
+        for d6 in 1..=6 {
+            let rank = tables.rank(location.career_table, affiliation, d6)?;
+            add(&mut ranks, rank, p / 6.0);
+            let resolved = resolve(tables, location, culture, affiliation, rank.clamp(0, 9))?;
+            first.push((resolved, p / 6.0));
+        }
+    }
+
+    // each reroll state leads to standings directly, or to other reroll states
+    let mut states: Vec<(i8, usize)> = vec![];
+    let mut direct: Vec<Vec<(Standing, f64)>> = vec![];
+    let mut next: Vec<Vec<((i8, usize), f64)>> = vec![];
+    let mut to_visit: Vec<(i8, usize)> = first
+        .iter()
+        .filter_map(|(resolved, _)| match resolved {
+            Resolved::Reroll(rank, disadvantage) => Some((*rank, *disadvantage)),
+            Resolved::Standing(_) => None,
+        })
+        .collect();
+    while let Some(state) = to_visit.pop() {
+        if states.contains(&state) {
+            continue;
+        }
+        let (rank, disadvantage) = state;
+        let mut to_standings = vec![];
+        let mut to_states = vec![];
+        for (affiliation, p) in affiliation_odds(disadvantage)? {
+            match resolve(tables, location, culture, affiliation, rank)? {
+                Resolved::Standing(standing) => add(&mut to_standings, standing, p),
+                Resolved::Reroll(rank, disadvantage) => {
+                    add(&mut to_states, (rank, disadvantage), p);
+                    to_visit.push((rank, disadvantage));
+                }
+            }
+        }
+        states.push(state);
+        direct.push(to_standings);
+        next.push(to_states);
+    }
+    let reroll_odds = solve(&states, &direct, &next)?;
+
+    let mut result = vec![];
+    for (resolved, p) in first {
+        match resolved {
+            Resolved::Standing(standing) => add(&mut result, standing, p),
+            Resolved::Reroll(rank, disadvantage) => {
+                let state = states
+                    .iter()
+                    .position(|&s| s == (rank, disadvantage))
+                    .expect("every reroll state was visited");
+                for (standing, q) in &reroll_odds[state] {
+                    add(&mut result, standing.clone(), p * q);
+                }
+            }
+        }
+    }
+    result.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    Ok(Odds {
+        rolled,
+        ranks,
+        standings: result,
+    })
+}
+
+// Writing `x[s]` for the odds of each standing after rerolling from state `s`,
+// `x[s] = direct[s] + sum over t of next[s][t] * x[t]`. That's `(I - next) x = direct`, solved
+// here by Gaussian elimination - there are only ever a handful of states.
+fn solve(
+    states: &[(i8, usize)],
+    direct: &[Vec<(Standing, f64)>],
+    next: &[Vec<((i8, usize), f64)>],
+) -> Result<Vec<Vec<(Standing, f64)>>, ChargenError> {
+    let n = states.len();
+    let mut standings: Vec<Standing> = vec![];
+    for (standing, _) in direct.iter().flatten() {
+        if !standings.contains(standing) {
+            standings.push(standing.clone());
+        }
+    }
+    let m = standings.len();
+
+    // each row is the n coefficients of (I - next), then the m columns of direct
+    let mut rows: Vec<Vec<f64>> = (0..n)
+        .map(|s| {
+            let mut row = vec![0.0; n + m];
+            row[s] = 1.0;
+            for (t, p) in &next[s] {
+                let t = states.iter().position(|state| state == t).unwrap();
+                row[t] -= p;
+            }
+            for (standing, p) in &direct[s] {
+                let j = standings.iter().position(|x| x == standing).unwrap();
+                row[n + j] += p;
+            }
+            row
+        })
+        .collect();
+
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&a, &b| rows[a][col].abs().total_cmp(&rows[b][col].abs()))
+            .unwrap();
+        if rows[pivot][col].abs() < 1e-12 {
+            // only happens if some rerolls can never land on a career
+            let (rank, _) = states[col];
+            return Err(ChargenError::BadTableEntry(format!(
+                "rerolling at rank {rank} never settles"
+            )));
+        }
+        rows.swap(col, pivot);
+        let scale = rows[col][col];
+        rows[col].iter_mut().for_each(|x| *x /= scale);
+        for r in 0..n {
+            if r != col && rows[r][col] != 0.0 {
+                let factor = rows[r][col];
+                let pivot_row = rows[col].clone();
+                for (x, p) in rows[r].iter_mut().zip(pivot_row) {
+                    *x -= factor * p;
+                }
+            }
+        }
+    }
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            standings
+                .iter()
+                .cloned()
+                .zip(row[n..].iter().copied())
+                .filter(|&(_, p)| p > 0.0)
+                .collect()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::locations::Demographic;
+    use crate::data::tables::bundled;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_d100() {
+        let one = d100_odds(1);
+        assert!(one[1..].iter().all(|&p| close(p, 0.01)));
+        // both tens dice share the units die: 100 is a 0 on the units and either tens die,
+        // and 10 is a 0 on the units with a 1 on both tens dice (a 0 would make it 100)
+        let two = d100_odds(2);
+        assert!(close(two.iter().sum(), 1.0));
+        assert!(close(two[100], 0.1 * (1.0 - 0.9 * 0.9)));
+        assert!(close(two[10], 0.1 * 0.1 * 0.1));
+    }
+
+    #[test]
+    fn test_solve() {
+        let standing = |affiliation| Standing {
+            affiliation,
+            rank: 0,
+            options: vec![],
+        };
+        let (a, b) = (standing(Affiliation::Army), standing(Affiliation::Watch));
+        // half of the plain rerolls land on the army, the rest reroll with disadvantage,
+        // a quarter of which land on the watch and the rest go back to rerolling plainly
+        let states = [(0, 0), (0, 1)];
+        let direct = [vec![(a.clone(), 0.5)], vec![(b.clone(), 0.25)]];
+        let next = [vec![((0, 1), 0.5)], vec![((0, 0), 0.75)]];
+        let x = solve(&states, &direct, &next).unwrap();
+        // x0 = a/2 + x1/2 and x1 = b/4 + 3x0/4, so x0 = 4a/5 + b/5 and x1 = 3a/5 + 2b/5
+        let odds_of = |row: &[(Standing, f64)], s: &Standing| sum_where(row, |t| t == s);
+        assert!(close(odds_of(&x[0], &a), 0.8));
+        assert!(close(odds_of(&x[0], &b), 0.2));
+        assert!(close(odds_of(&x[1], &a), 0.6));
+        assert!(close(odds_of(&x[1], &b), 0.4));
+
+        // rerolls that only ever reroll never settle
+        let stuck = solve(&states[..1], &[vec![]], &[vec![((0, 0), 1.0)]]);
+        assert!(stuck.is_err());
+    }
+
+    #[test]
+    fn test_duruza() {
+        let tables = bundled();
+        let duruza = tables.location_by_id("duruza", Demographic::Urban).unwrap();
+        let odds = odds(tables, &duruza, Culture::Varlish).unwrap();
+        // gentry are 1-10 on the d100
+        assert!(close(odds.affiliation(Affiliation::Gentry), 0.1));
+        let total: f64 = odds.standings.iter().map(|(_, p)| p).sum();
+        assert!(close(total, 1.0));
+        assert!(close(odds.ranks.iter().map(|(_, p)| p).sum(), 1.0));
+
+        // outsiders roll with disadvantage: both tens dice have to land low
+        let outsiders = super::odds(tables, &duruza, Culture::Revic).unwrap();
+        assert!(close(outsiders.affiliation(Affiliation::Gentry), 0.01));
+        // and can't be offered the careers for the kremish
+        let dewisetic = Career::from_id("dewisetic").unwrap();
+        assert_eq!(outsiders.career(dewisetic), 0.0);
+        let kremish = super::odds(tables, &duruza, Culture::Kremish).unwrap();
+        assert!(kremish.career(dewisetic) > 0.0);
+    }
+
+    #[test]
+    fn test_rerolls_settle() {
+        // every birthplace, for every culture that lives there, adds up
+        let tables = bundled();
+        for (_, birthplace) in tables.birthplaces() {
+            let location = tables
+                .location_by_id(&birthplace.id, Demographic::Border)
+                .unwrap();
+            for culture in [location.culture, location.secondary_culture] {
+                let odds = odds(tables, &location, culture).unwrap();
+                let total: f64 = odds.standings.iter().map(|(_, p)| p).sum();
+                assert!(close(total, 1.0), "{} adds up to {total}", location.name);
+            }
+        }
+    }
+}
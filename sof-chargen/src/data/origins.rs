@@ -0,0 +1,235 @@
+// The career tables answer "what can I be, given where I'm from?". Players tend to ask it the
+// other way round - "how do I end up a Temple Guardian?" - so this walks the same tables
+// backwards: every affiliation and rank on every table, followed through its redirects the way
+// `change_rank` would, keeping the ones that land on what was asked about.
+use crate::data::careers::{
+    AFFILIATIONS, Affiliation, Career, CareerClass, CareerTableEntry, CareerTableStar,
+};
+use crate::data::locations::{CAREER_TABLES, CareerTable};
+use crate::data::tables::Tables;
+use std::fmt::{Display, Formatter};
+
+/// What to look for: one career, or any career of a class
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Target {
+    Career(Career),
+    Class(CareerClass),
+}
+
+impl Target {
+    fn matches(&self, career: Career) -> bool {
+        match self {
+            Target::Career(target) => *target == career,
+            Target::Class(class) => career.class() == *class,
+        }
+    }
+}
+
+impl From<Career> for Target {
+    fn from(career: Career) -> Self {
+        Target::Career(career)
+    }
+}
+
+impl From<CareerClass> for Target {
+    fn from(class: CareerClass) -> Self {
+        Target::Class(class)
+    }
+}
+
+impl Display for Target {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Target::Career(career) => write!(f, "{career}"),
+            Target::Class(class) => write!(f, "any {class}"),
+        }
+    }
+}
+
+/// One affiliation and rank that ends in the career
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Route {
+    pub table: CareerTable,
+    pub affiliation: Affiliation,
+    pub rank: i8,
+    /// Where the redirects from the affiliation and rank went, ending where the career is written.
+    /// Empty if it's written right there.
+    pub redirects: Vec<(Affiliation, i8)>,
+    pub career: Career,
+    /// What the career itself needs
+    pub star: CareerTableStar,
+    /// What you need to be in the affiliation the career is written under
+    pub affiliation_star: CareerTableStar,
+}
+
+/// Everywhere a career can come from
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Origins {
+    pub target: Target,
+    pub routes: Vec<Route>,
+    /// The names of the birthplaces using each table that has a route, in table order
+    pub birthplaces: Vec<(CareerTable, Vec<String>)>,
+}
+
+// follows redirects until landing on careers, or giving up on a reroll or loop
+fn follow(
+    tables: &Tables,
+    table: CareerTable,
+    mut affiliation: Affiliation,
+    mut rank: i8,
+) -> Option<(Vec<(Affiliation, i8)>, CareerTableEntry)> {
+    let mut redirects = vec![];
+    loop {
+        match tables.careers(table, affiliation, rank).ok()? {
+            CareerTableEntry::RemainAtRank(r) => rank = r,
+            CareerTableEntry::ChangeAffiliation(a) => affiliation = a,
+            // a reroll could land anywhere, so it isn't a route to anything in particular
+            CareerTableEntry::Reroll | CareerTableEntry::RerollWithDisadvantage => return None,
+            entry => return Some((redirects, entry)),
+        }
+        // the tables are linted for loops, but don't hang if handed some that aren't
+        if redirects.contains(&(affiliation, rank)) {
+            return None;
+        }
+        redirects.push((affiliation, rank));
+    }
+}
+
+/// Every affiliation and rank, on every table, that leads to the target,
+/// and the birthplaces that use those tables
+pub fn origins(tables: &Tables, target: impl Into<Target>) -> Origins {
+    let target = target.into();
+    let mut routes = vec![];
+    for table in CAREER_TABLES {
+        for affiliation in AFFILIATIONS {
+            for rank in 0..=9 {
+                let Some((redirects, entry)) = follow(tables, table, affiliation, rank) else {
+                    continue;
+                };
+                let options = match entry {
+                    CareerTableEntry::Career(career, star) => vec![(career, star)],
+                    CareerTableEntry::Careers(c1, c2) => vec![c1, c2],
+                    _ => vec![],
+                };
+                let written_under = redirects.last().map_or(affiliation, |&(a, _)| a);
+                for (career, star) in options {
+                    if target.matches(career) {
+                        routes.push(Route {
+                            table,
+                            affiliation,
+                            rank,
+                            redirects: redirects.clone(),
+                            career,
+                            star,
+                            affiliation_star: tables.star(table, written_under),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    let birthplaces = CAREER_TABLES
+        .into_iter()
+        .filter(|&table| routes.iter().any(|route| route.table == table))
+        .map(|table| {
+            let names = tables
+                .birthplaces()
+                .filter(|(_, birthplace)| birthplace.career_table == table)
+                .map(|(_, birthplace)| birthplace.name.clone())
+                .collect();
+            (table, names)
+        })
+        .collect();
+
+    Origins {
+        target,
+        routes,
+        birthplaces,
+    }
+}
+
+impl Display for Origins {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.routes.is_empty() {
+            return write!(f, "Nothing leads to {}.", self.target);
+        }
+        write!(f, "{} comes from:", self.target)?;
+        for route in &self.routes {
+            write!(
+                f,
+                "\n - {:?} {} at rank {}",
+                route.table, route.affiliation, route.rank
+            )?;
+            for (affiliation, rank) in &route.redirects {
+                write!(f, " -> {affiliation} at rank {rank}")?;
+            }
+            write!(f, ": {}", route.career)?;
+            for star in [route.affiliation_star, route.star] {
+                if star != CareerTableStar::None {
+                    write!(f, ", {star}")?;
+                }
+            }
+        }
+        for (table, names) in &self.birthplaces {
+            write!(f, "\nBorn into the {table:?} table: {}", names.join("; "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::locations::{Culture, Faith};
+    use crate::data::tables::bundled;
+
+    #[test]
+    fn test_dewisetic() {
+        let dewisetic = Career::from_id("dewisetic").unwrap();
+        let origins = origins(bundled(), dewisetic);
+        assert!(!origins.routes.is_empty());
+        for route in &origins.routes {
+            assert_eq!(route.career, dewisetic);
+            assert_eq!(
+                route.star,
+                CareerTableStar::NeedsFaithAndCulture(Faith::Gytungrug, Culture::Kremish)
+            );
+        }
+        // slum folk at rank 3 in the empire have it written right there
+        assert!(origins.routes.contains(&Route {
+            table: CareerTable::ValiantEmpire,
+            affiliation: Affiliation::Slumfolk,
+            rank: 3,
+            redirects: vec![],
+            career: dewisetic,
+            star: CareerTableStar::NeedsFaithAndCulture(Faith::Gytungrug, Culture::Kremish),
+            affiliation_star: CareerTableStar::None,
+        }));
+        let (table, names) = &origins.birthplaces[0];
+        assert_eq!(*table, CareerTable::ValiantEmpire);
+        assert!(names.contains(&"The Imperial Capital of Duruza".to_string()));
+    }
+
+    #[test]
+    fn test_redirects_and_classes() {
+        // some of the ways to nobility go through other affiliations or ranks first,
+        // and those should end where a career is actually written
+        let routes = origins(bundled(), CareerClass::Noble).routes;
+        assert!(
+            routes
+                .iter()
+                .all(|route| route.career.class() == CareerClass::Noble)
+        );
+        assert!(routes.iter().any(|route| !route.redirects.is_empty()));
+        for route in &routes {
+            if let Some(&(affiliation, rank)) = route.redirects.last() {
+                let entry = bundled().careers(route.table, affiliation, rank).unwrap();
+                assert!(matches!(
+                    entry,
+                    CareerTableEntry::Career(..) | CareerTableEntry::Careers(..)
+                ));
+            }
+        }
+    }
+}
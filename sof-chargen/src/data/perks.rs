@@ -18,6 +18,8 @@ use subenum::subenum;
 use crate::data::careers::CareerClass;
 use crate::data::locations::Language;
 use crate::{Backend, Stat};
+use std::fmt;
+use std::fmt::{Display, Formatter};
 
 #[derive(Clone, Default, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct Perks {
@@ -29,6 +31,11 @@ pub struct Perks {
 }
 
 impl Perks {
+    /// The ten slots for a core stat, filled or not
+    pub fn slots(&self, stat: Stat) -> &[PerkSlot; 10] {
+        self.get_table(stat)
+    }
+
     fn get_table(&self, stat: Stat) -> &[PerkSlot; 10] {
         match stat {
             Stat::Arms => &self.arms,
@@ -51,7 +58,7 @@ impl Perks {
             _ => panic!("tried to gain perk in non-core stat!"), // todo consider sub-enums here
         }
     }
-    fn insert_perk(
+    pub(crate) fn insert_perk(
         &mut self,
         stat: Stat,
         perk: Perk,
@@ -79,7 +86,7 @@ impl Perks {
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
-enum PerkSlot {
+pub enum PerkSlot {
     None,
     Perk(Perk, Option<CareerClass>),
     Wound(String),
@@ -93,6 +100,17 @@ impl Default for PerkSlot {
     }
 }
 
+impl Display for PerkSlot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PerkSlot::None => Ok(()),
+            PerkSlot::Perk(perk, Some(class)) => write!(f, "{perk} ({class})"),
+            PerkSlot::Perk(perk, None) => write!(f, "{perk}"),
+            PerkSlot::Wound(wound) => write!(f, "Wound: {wound}"),
+        }
+    }
+}
+
 #[subenum(ArmsPerk, FacePerk, HandsPerk, LegsPerk, MindPerk)]
 #[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
 pub enum Perk {
@@ -360,6 +378,21 @@ fn can_take_perk(perk: Perk, backend: &impl Backend) -> CanTakePerk {
     False
 }
 
+// the variant names, spaced out: FightingStyle(NobleDuelling) is "Fighting Style (Noble Duelling)"
+impl Display for Perk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut previous = ' ';
+        for c in format!("{self:?}").chars().filter(|&c| c != '"') {
+            if (c.is_uppercase() && previous.is_lowercase()) || c == '(' {
+                write!(f, " ")?;
+            }
+            write!(f, "{c}")?;
+            previous = c;
+        }
+        Ok(())
+    }
+}
+
 impl Perk {
     fn take_effect(&self, backend: &impl Backend) {
         match self {
@@ -0,0 +1,334 @@
+// Nearly every table in the book works the same way: roll a die, find the row whose range the
+// roll falls in, and either take what it says or roll again on a smaller table inside it.
+// `RollTable` is that, once, so each table only has to say what's on it.
+use crate::ChargenError;
+use crate::dice::{D, DiceRoll};
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
+
+/// The die a table is rolled with
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize)]
+pub enum Die {
+    D3,
+    D6,
+    D100,
+}
+
+impl Die {
+    /// Everything the die can land on
+    pub fn faces(&self) -> RangeInclusive<i8> {
+        match self {
+            Die::D3 => 1..=3,
+            Die::D6 => 1..=6,
+            Die::D100 => 1..=100,
+        }
+    }
+
+    pub fn roll(&self) -> i8 {
+        match self {
+            Die::D3 => D::<3>::roll().result(),
+            Die::D6 => D::<6>::roll().result(),
+            Die::D100 => D::<100>::roll().result(),
+        }
+    }
+}
+
+impl Display for Die {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Die::D3 => write!(f, "d3"),
+            Die::D6 => write!(f, "d6"),
+            Die::D100 => write!(f, "d100"),
+        }
+    }
+}
+
+/// One line of a table: the rolls (inclusive) that land on it, and what you get
+#[derive(Clone, Debug, Deserialize)]
+pub struct Row<T> {
+    pub rolls: (i8, i8),
+    pub entry: T,
+}
+
+impl<T> Row<T> {
+    pub fn contains(&self, roll: i8) -> bool {
+        (self.rolls.0..=self.rolls.1).contains(&roll)
+    }
+}
+
+/// Finds the row a roll lands on
+pub fn lookup<'a, T>(
+    rows: &'a [Row<T>],
+    table: &'static str,
+    roll: i8,
+) -> Result<&'a T, ChargenError> {
+    rows.iter()
+        .find(|row| row.contains(roll))
+        .map(|row| &row.entry)
+        .ok_or(ChargenError::OffTable { table, roll })
+}
+
+/// Every roll in `rolls` that lands on something other than exactly one row,
+/// along with how many rows it does land on
+pub fn coverage<T>(rows: &[Row<T>], rolls: RangeInclusive<i8>) -> Vec<(i8, usize)> {
+    rolls
+        .filter_map(|roll| {
+            let hits = rows.iter().filter(|row| row.contains(roll)).count();
+            (hits != 1).then_some((roll, hits))
+        })
+        .collect()
+}
+
+/// A table you roll on
+#[derive(Clone, Debug, Deserialize)]
+pub struct RollTable<T> {
+    pub die: Die,
+    pub rows: Vec<Row<T>>,
+}
+
+impl<T> RollTable<T> {
+    pub fn new(die: Die, rows: impl IntoIterator<Item = ((i8, i8), T)>) -> Self {
+        Self {
+            die,
+            rows: rows
+                .into_iter()
+                .map(|(rolls, entry)| Row { rolls, entry })
+                .collect(),
+        }
+    }
+
+    /// What a roll lands on, with `table` naming the table if it's off the end
+    pub fn get(&self, table: &'static str, roll: i8) -> Result<&T, ChargenError> {
+        lookup(&self.rows, table, roll)
+    }
+
+    /// Rolls the table's die, returning the roll and what it landed on
+    pub fn roll(&self, table: &'static str) -> Result<(i8, &T), ChargenError> {
+        let roll = self.die.roll();
+        Ok((roll, self.get(table, roll)?))
+    }
+
+    /// Each row's range of rolls and entry, in the order they're written
+    pub fn list(&self) -> impl Iterator<Item = (RangeInclusive<i8>, &T)> {
+        self.rows
+            .iter()
+            .map(|row| (row.rolls.0..=row.rolls.1, &row.entry))
+    }
+
+    /// Every face of the die that lands on something other than exactly one row
+    pub fn coverage(&self) -> Vec<(i8, usize)> {
+        coverage(&self.rows, self.die.faces())
+    }
+
+    pub fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> RollTable<U> {
+        RollTable {
+            die: self.die,
+            rows: self
+                .rows
+                .iter()
+                .map(|row| Row {
+                    rolls: row.rolls,
+                    entry: f(&row.entry),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<T: Display> RollTable<T> {
+    /// The table as markdown, the way it'd be printed in the book
+    pub fn render(&self) -> String {
+        let mut table = format!("| {} | Result |\n| --- | --- |\n", self.die);
+        for (rolls, entry) in self.list() {
+            table += &format!("| {} | {entry} |\n", render_rolls(&rolls));
+        }
+        table
+    }
+}
+
+fn render_rolls(rolls: &RangeInclusive<i8>) -> String {
+    if rolls.start() == rolls.end() {
+        rolls.start().to_string()
+    } else {
+        format!("{}-{}", rolls.start(), rolls.end())
+    }
+}
+
+/// An entry on a table that might send you to roll again on another one
+#[derive(Clone, Debug, Deserialize)]
+pub enum Nested<T> {
+    Entry(T),
+    RollAgain(RollTable<Nested<T>>),
+}
+
+impl<T> RollTable<Nested<T>> {
+    /// Follows the rolls through however many tables they take, one roll per table.
+    /// Rolls that aren't needed are ignored.
+    pub fn get_nested(&self, table: &'static str, rolls: &[i8]) -> Result<&T, ChargenError> {
+        let mut current = self;
+        for &roll in rolls {
+            match current.get(table, roll)? {
+                Nested::Entry(entry) => return Ok(entry),
+                Nested::RollAgain(next) => current = next,
+            }
+        }
+        Err(ChargenError::NotRolled(table))
+    }
+
+    /// Follows rolls from `roll` through however many tables they take, rolling no more than that
+    pub fn get_rolling(
+        &self,
+        table: &'static str,
+        mut roll: impl FnMut() -> i8,
+    ) -> Result<&T, ChargenError> {
+        let mut current = self;
+        loop {
+            match current.get(table, roll())? {
+                Nested::Entry(entry) => return Ok(entry),
+                Nested::RollAgain(next) => current = next,
+            }
+        }
+    }
+
+    /// Rolls until landing on an entry, returning the rolls it took
+    pub fn roll_nested(&self, table: &'static str) -> Result<(Vec<i8>, &T), ChargenError> {
+        let mut rolls = vec![];
+        let mut current = self;
+        loop {
+            let (roll, entry) = current.roll(table)?;
+            rolls.push(roll);
+            match entry {
+                Nested::Entry(entry) => return Ok((rolls, entry)),
+                Nested::RollAgain(next) => current = next,
+            }
+        }
+    }
+
+    /// Every entry on this table and the ones inside it
+    pub fn entries(&self) -> Vec<&T> {
+        self.rows
+            .iter()
+            .flat_map(|row| match &row.entry {
+                Nested::Entry(entry) => vec![entry],
+                Nested::RollAgain(table) => table.entries(),
+            })
+            .collect()
+    }
+
+    /// Like `map`, but reaching into the tables inside it too
+    pub fn map_nested<U>(&self, f: &impl Fn(&T) -> U) -> RollTable<Nested<U>> {
+        self.map(|entry| match entry {
+            Nested::Entry(entry) => Nested::Entry(f(entry)),
+            Nested::RollAgain(table) => Nested::RollAgain(table.map_nested(f)),
+        })
+    }
+
+    /// Like `coverage`, but for this table and every one inside it
+    pub fn nested_coverage(&self) -> Vec<(i8, usize)> {
+        let mut gaps = self.coverage();
+        for row in &self.rows {
+            if let Nested::RollAgain(table) = &row.entry {
+                gaps.extend(table.nested_coverage());
+            }
+        }
+        gaps
+    }
+}
+
+impl<T: Display> Display for Nested<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Nested::Entry(entry) => entry.fmt(f),
+            Nested::RollAgain(table) => {
+                write!(f, "roll a {}:", table.die)?;
+                for (i, (rolls, entry)) in table.list().enumerate() {
+                    let sep = if i == 0 { "" } else { ";" };
+                    write!(f, "{sep} {} {entry}", render_rolls(&rolls))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::locations::{culture_table, faith_table, further_afield_culture_table};
+    use crate::dice;
+
+    fn table() -> RollTable<Nested<&'static str>> {
+        RollTable::new(
+            Die::D6,
+            [
+                ((1, 3), Nested::Entry("low")),
+                ((4, 5), Nested::Entry("high")),
+                (
+                    (6, 6),
+                    Nested::RollAgain(RollTable::new(
+                        Die::D6,
+                        [
+                            ((1, 5), Nested::Entry("almost")),
+                            ((6, 6), Nested::Entry("six")),
+                        ],
+                    )),
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_lookup() {
+        let table = table();
+        assert_eq!(table.get_nested("test", &[2, 6]), Ok(&"low"));
+        assert_eq!(table.get_nested("test", &[6, 6]), Ok(&"six"));
+        assert_eq!(
+            table.get_nested("test", &[7]),
+            Err(ChargenError::OffTable {
+                table: "test",
+                roll: 7
+            })
+        );
+        assert_eq!(
+            table.get_nested("test", &[6]),
+            Err(ChargenError::NotRolled("test"))
+        );
+        assert_eq!(table.entries(), [&"low", &"high", &"almost", &"six"]);
+
+        // only the rolls that are needed are made
+        let mut rolls = [2, 6].into_iter();
+        assert_eq!(
+            table.get_rolling("test", || rolls.next().unwrap()),
+            Ok(&"low")
+        );
+        assert_eq!(rolls.next(), Some(6));
+
+        dice::script([6, 2]);
+        assert_eq!(table.roll_nested("test"), Ok((vec![6, 2], &"almost")));
+        dice::unscript();
+    }
+
+    #[test]
+    fn test_coverage() {
+        assert!(table().nested_coverage().is_empty());
+        // the tables written out in code, rather than loaded from files
+        assert!(further_afield_culture_table().nested_coverage().is_empty());
+        assert!(culture_table().nested_coverage().is_empty());
+        assert!(faith_table().nested_coverage().is_empty());
+        let gappy = RollTable::new(Die::D3, [((1, 2), "a"), ((2, 2), "b")]);
+        assert_eq!(gappy.coverage(), [(2, 2), (3, 0)]);
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            table().render(),
+            "| d6 | Result |\n\
+             | --- | --- |\n\
+             | 1-3 | low |\n\
+             | 4-5 | high |\n\
+             | 6 | roll a d6: 1-5 almost; 6 six |\n"
+        );
+    }
+}
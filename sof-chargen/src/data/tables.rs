@@ -0,0 +1,499 @@
+// The birthplace and career tables live in RON files next to this one, so the rules can change
+// without anyone touching the code. They're bundled into the crate, parsed and checked the first
+// time they're needed; `get_careers` and friends are just lookups into them.
+use crate::ChargenError;
+use crate::data::careers::{Affiliation, Career, CareerClass, CareerTableEntry, CareerTableStar};
+use crate::data::lint::{Finding, lint};
+use crate::data::locations::{CareerTable, Culture, Demographic, Faith, Location};
+use crate::data::roll_table::{Nested, RollTable, Row, coverage, lookup};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::sync::LazyLock;
+
+const BIRTHPLACES: &str = include_str!("birthplaces.ron");
+const CAREERS: &str = include_str!("careers.ron");
+const CAREER_LIST: &str = include_str!("career_list.ron");
+
+// the careers the tables pick from, which everything else refers to by index
+static BUNDLED_CAREERS: LazyLock<Vec<CareerInfo>> =
+    LazyLock::new(|| match career_list_from_ron(CAREER_LIST) {
+        Ok(careers) => careers,
+        Err(e) => panic!("the bundled career list is broken: {e}"),
+    });
+
+static BUNDLED: LazyLock<Tables> = LazyLock::new(|| match Tables::from_ron(BIRTHPLACES, CAREERS) {
+    Ok(tables) => tables,
+    Err(e) => panic!("the bundled tables are broken: {e}"),
+});
+
+/// The tables that ship with the crate
+pub fn bundled() -> &'static Tables {
+    &BUNDLED
+}
+
+/// Every career, in the order `Career`s index them
+pub fn career_list() -> &'static [CareerInfo] {
+    &BUNDLED_CAREERS
+}
+
+/// A line of career_list.ron
+#[derive(Clone, Debug, Deserialize)]
+pub struct CareerInfo {
+    pub id: String,
+    pub name: String,
+    pub class: CareerClass,
+}
+
+fn career_list_from_ron(text: &str) -> Result<Vec<CareerInfo>, TableError> {
+    let careers: Vec<CareerInfo> = ron::from_str(text).map_err(|error| TableError::Parse {
+        file: "career list",
+        error,
+    })?;
+    let findings = duplicate_ids("career", careers.iter().map(|info| info.id.as_str()));
+    if findings.is_empty() {
+        Ok(careers)
+    } else {
+        Err(TableError::Invalid(findings))
+    }
+}
+
+// ids are what saves hold on to, so two things can't share one
+fn duplicate_ids<'a>(kind: &'static str, ids: impl Iterator<Item = &'a str>) -> Vec<Finding> {
+    let mut seen = HashSet::new();
+    let mut findings = vec![];
+    for id in ids {
+        if !seen.insert(id) {
+            findings.push(Finding::DuplicateId {
+                kind,
+                id: id.to_string(),
+            });
+        }
+    }
+    findings
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Region {
+    pub name: String,
+    #[serde(default)]
+    pub far_afield: bool,
+    pub places: RollTable<Nested<Birthplace>>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Birthplace {
+    pub id: String,
+    pub name: String,
+    pub culture: Culture,
+    pub secondary_culture: Option<Culture>,
+    pub faith: Faith,
+    pub secondary_faith: Option<Faith>,
+    // rolled on a d3 if not given
+    pub demographic: Option<Demographic>,
+    pub career_table: CareerTable,
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl Display for Birthplace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub enum RankDie {
+    D3(i8),
+    D6(i8),
+}
+
+impl RankDie {
+    pub fn rank(&self, d6: i8) -> i8 {
+        match self {
+            RankDie::D3(plus) => (d6 + 1) / 2 + plus, // rounds up so 1,2 -> 1, 3,4 -> 2, etc
+            RankDie::D6(plus) => d6 + plus,
+        }
+    }
+}
+
+// careers are written as ("id") in the files, with an optional star on the end
+#[derive(Copy, Clone, Debug, Deserialize)]
+struct CareerOption(Career, #[serde(default)] CareerTableStar);
+
+impl From<CareerOption> for (Career, CareerTableStar) {
+    fn from(CareerOption(career, star): CareerOption) -> Self {
+        (career, star)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+enum EntryData {
+    Career(CareerOption),
+    Careers(CareerOption, CareerOption),
+    RemainAtRank(i8),
+    ChangeAffiliation(Affiliation),
+    RerollWithDisadvantage,
+    Reroll,
+}
+
+impl From<EntryData> for CareerTableEntry {
+    fn from(entry: EntryData) -> Self {
+        match entry {
+            EntryData::Career(option) => {
+                let (career, star) = option.into();
+                CareerTableEntry::Career(career, star)
+            }
+            EntryData::Careers(o1, o2) => CareerTableEntry::Careers(o1.into(), o2.into()),
+            EntryData::RemainAtRank(rank) => CareerTableEntry::RemainAtRank(rank),
+            EntryData::ChangeAffiliation(a) => CareerTableEntry::ChangeAffiliation(a),
+            EntryData::RerollWithDisadvantage => CareerTableEntry::RerollWithDisadvantage,
+            EntryData::Reroll => CareerTableEntry::Reroll,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct AffiliationData {
+    pub rank: RankDie,
+    #[serde(default)]
+    pub star: CareerTableStar,
+    #[serde(deserialize_with = "careers")]
+    pub careers: Vec<Row<CareerTableEntry>>,
+}
+
+fn careers<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Row<CareerTableEntry>>, D::Error> {
+    let rows: Vec<Row<EntryData>> = Deserialize::deserialize(deserializer)?;
+    Ok(rows
+        .into_iter()
+        .map(|row| Row {
+            rolls: row.rolls,
+            entry: row.entry.into(),
+        })
+        .collect())
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct CareerTableData {
+    pub affiliation_rolls: HashMap<Demographic, RollTable<Affiliation>>,
+    pub affiliations: HashMap<Affiliation, AffiliationData>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Tables {
+    pub birthplaces: RollTable<Region>,
+    pub career_tables: HashMap<CareerTable, CareerTableData>,
+}
+
+#[derive(Debug)]
+pub enum TableError {
+    Parse {
+        file: &'static str,
+        error: ron::error::SpannedError,
+    },
+    Invalid(Vec<Finding>),
+}
+
+impl Display for TableError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TableError::Parse { file, error } => write!(f, "couldn't read the {file}: {error}"),
+            TableError::Invalid(findings) => {
+                write!(f, "the tables have problems:")?;
+                for finding in findings {
+                    write!(f, "\n - {finding}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for TableError {}
+
+impl Tables {
+    /// Reads the birthplace and career tables from RON, and checks they hold together.
+    /// Careers are looked up by id in the bundled career list.
+    pub fn from_ron(birthplaces: &str, careers: &str) -> Result<Self, TableError> {
+        let tables = Tables {
+            birthplaces: ron::from_str(birthplaces).map_err(|error| TableError::Parse {
+                file: "birthplace table",
+                error,
+            })?,
+            career_tables: ron::from_str(careers).map_err(|error| TableError::Parse {
+                file: "career tables",
+                error,
+            })?,
+        };
+        let findings: Vec<Finding> = tables
+            .validate()
+            .into_iter()
+            .filter(Finding::is_error)
+            .collect();
+        if findings.is_empty() {
+            Ok(tables)
+        } else {
+            Err(TableError::Invalid(findings))
+        }
+    }
+
+    /// Everything that's wrong with the tables, or just worth knowing about
+    pub fn validate(&self) -> Vec<Finding> {
+        let mut findings = vec![];
+        covers(self.birthplaces.coverage(), "birthplace", &mut findings);
+        findings.extend(duplicate_ids(
+            "birthplace",
+            self.birthplaces()
+                .map(|(_, birthplace)| birthplace.id.as_str()),
+        ));
+        for (_, region) in self.birthplaces.list() {
+            covers(region.places.nested_coverage(), &region.name, &mut findings);
+            for birthplace in region.places.entries() {
+                if !region.far_afield && birthplace.demographic.is_none() {
+                    findings.push(Finding::NoDemographic {
+                        place: birthplace.name.clone(),
+                    });
+                }
+                if !self.career_tables.contains_key(&birthplace.career_table) {
+                    findings.push(Finding::MissingCareerTable {
+                        table: birthplace.career_table,
+                    });
+                }
+            }
+        }
+
+        for (&table, data) in &self.career_tables {
+            for demographic in crate::data::locations::DEMOGRAPHICS {
+                match data.affiliation_rolls.get(&demographic) {
+                    Some(rolls) => covers(
+                        rolls.coverage(),
+                        &format!("{table:?} {demographic:?} affiliation"),
+                        &mut findings,
+                    ),
+                    None => findings.push(Finding::MissingAffiliationRoll { table, demographic }),
+                }
+            }
+            for affiliation in crate::data::careers::AFFILIATIONS {
+                match data.affiliations.get(&affiliation) {
+                    Some(a) => covers(
+                        coverage(&a.careers, 0..=9),
+                        &format!("{table:?} {affiliation}"),
+                        &mut findings,
+                    ),
+                    None => findings.push(Finding::MissingAffiliation { table, affiliation }),
+                }
+            }
+        }
+        findings.dedup();
+
+        findings.extend(lint(self));
+        findings
+    }
+
+    /// Every birthplace, along with the region it's in
+    pub fn birthplaces(&self) -> impl Iterator<Item = (&Region, &Birthplace)> {
+        self.birthplaces.list().flat_map(|(_, region)| {
+            region
+                .places
+                .entries()
+                .into_iter()
+                .map(move |birthplace| (region, birthplace))
+        })
+    }
+
+    pub fn location(&self, d6s: (i8, i8, i8), d3: i8) -> Result<Location, ChargenError> {
+        let region = self.birthplaces.get("birthplace", d6s.0)?;
+        let birthplace = region.places.get_nested("birthplace", &[d6s.1, d6s.2])?;
+        let demographic = match birthplace.demographic {
+            Some(demographic) => demographic,
+            None => match d3 {
+                1 => Demographic::Urban,
+                2 => Demographic::Rural,
+                3 => Demographic::Border,
+                _ => {
+                    return Err(ChargenError::OffTable {
+                        table: "demographic",
+                        roll: d3,
+                    });
+                }
+            },
+        };
+        Ok(Self::to_location(region, birthplace, demographic))
+    }
+
+    /// The birthplace with this id, with its demographic as rolled
+    pub fn location_by_id(&self, id: &str, demographic: Demographic) -> Option<Location> {
+        self.birthplaces()
+            .find(|(_, birthplace)| birthplace.id == id)
+            .map(|(region, birthplace)| Self::to_location(region, birthplace, demographic))
+    }
+
+    fn to_location(region: &Region, birthplace: &Birthplace, demographic: Demographic) -> Location {
+        Location {
+            id: birthplace.id.clone(),
+            name: birthplace.name.clone(),
+            culture: birthplace.culture,
+            // places that don't list these never use the secondary faith/culture mechanic
+            secondary_culture: birthplace.secondary_culture.unwrap_or(birthplace.culture),
+            faith: birthplace.faith,
+            secondary_faith: birthplace.secondary_faith.unwrap_or(birthplace.faith),
+            demographic,
+            career_table: birthplace.career_table,
+            far_afield: region.far_afield,
+        }
+    }
+
+    fn career_table(&self, table: CareerTable) -> Result<&CareerTableData, ChargenError> {
+        self.career_tables
+            .get(&table)
+            .ok_or_else(|| ChargenError::BadTableEntry(format!("there's no {table:?} table")))
+    }
+
+    fn affiliation_data(
+        &self,
+        table: CareerTable,
+        affiliation: Affiliation,
+    ) -> Result<&AffiliationData, ChargenError> {
+        self.career_table(table)?
+            .affiliations
+            .get(&affiliation)
+            .ok_or_else(|| {
+                ChargenError::BadTableEntry(format!("the {table:?} table has no {affiliation}"))
+            })
+    }
+
+    pub fn affiliation(
+        &self,
+        table: CareerTable,
+        demographic: Demographic,
+        d100: i8,
+    ) -> Result<Affiliation, ChargenError> {
+        let rolls = self
+            .career_table(table)?
+            .affiliation_rolls
+            .get(&demographic)
+            .ok_or_else(|| {
+                ChargenError::BadTableEntry(format!(
+                    "the {table:?} table has no affiliations for {demographic:?} places"
+                ))
+            })?;
+        rolls.get("affiliation", d100).copied()
+    }
+
+    pub fn rank(
+        &self,
+        table: CareerTable,
+        affiliation: Affiliation,
+        d6: i8,
+    ) -> Result<i8, ChargenError> {
+        Ok(self.affiliation_data(table, affiliation)?.rank.rank(d6))
+    }
+
+    pub fn star(&self, table: CareerTable, affiliation: Affiliation) -> CareerTableStar {
+        self.affiliation_data(table, affiliation)
+            .map_or(CareerTableStar::None, |data| data.star)
+    }
+
+    pub fn careers(
+        &self,
+        table: CareerTable,
+        affiliation: Affiliation,
+        rank: i8,
+    ) -> Result<CareerTableEntry, ChargenError> {
+        let rows = &self.affiliation_data(table, affiliation)?.careers;
+        lookup(rows, "career", rank).cloned()
+    }
+}
+
+// every roll on a table should land on exactly one row
+fn covers(gaps: Vec<(i8, usize)>, table: &str, findings: &mut Vec<Finding>) {
+    findings.extend(gaps.into_iter().map(|(roll, rows)| Finding::Coverage {
+        table: table.to_string(),
+        roll,
+        rows,
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled() {
+        let tables = bundled();
+        assert_eq!(
+            tables.location((1, 1, 1), 1).unwrap().name,
+            "The Imperial Capital of Duruza"
+        );
+        let rezankath = tables.location((6, 6, 6), 3).unwrap();
+        assert_eq!(rezankath.demographic, Demographic::Border);
+        assert_eq!(rezankath.secondary_faith, Faith::TempleOfSeraf);
+        assert!(rezankath.far_afield);
+        assert!(
+            tables
+                .birthplaces
+                .get("birthplace", 6)
+                .unwrap()
+                .places
+                .render()
+                .ends_with("| 6 | roll a d6: 1 The hidebound, riven Rose Coast; 2 The plague-blighted Crown Coast; 3 A city clinging to the Umbral Coast; 4 The blasted dunes of the Great Flat; 5 Neminyah, the rotting heart; 6 The brackish jungles of Rezankath |\n")
+        );
+        assert_eq!(
+            tables.location((1, 7, 1), 1).unwrap_err(),
+            ChargenError::OffTable {
+                table: "birthplace",
+                roll: 7
+            }
+        );
+    }
+
+    #[test]
+    fn test_bad_tables() {
+        // a gap in the birthplaces, and a slum that redirects to itself
+        let birthplaces = BIRTHPLACES.replace("rolls: (4, 5)", "rolls: (4, 4)");
+        let careers = CAREERS.replacen(
+            "(rolls: (4, 9), entry: ChangeAffiliation(Plebeians))",
+            "(rolls: (4, 9), entry: RemainAtRank(4))",
+            1,
+        );
+        let Err(TableError::Invalid(findings)) = Tables::from_ron(&birthplaces, &careers) else {
+            panic!("the tables should have been rejected");
+        };
+        assert_eq!(
+            findings,
+            [
+                Finding::Coverage {
+                    table: "birthplace".to_string(),
+                    roll: 5,
+                    rows: 0
+                },
+                Finding::Cycle {
+                    table: CareerTable::ValiantEmpire,
+                    path: vec![(Affiliation::Slumfolk, 4)]
+                }
+            ]
+        );
+
+        let list = CAREER_LIST.replace("\"archer\"", "\"apothecary\"");
+        let Err(TableError::Invalid(findings)) = career_list_from_ron(&list) else {
+            panic!("the career list should have been rejected");
+        };
+        assert_eq!(
+            findings,
+            [Finding::DuplicateId {
+                kind: "career",
+                id: "apothecary".to_string()
+            }]
+        );
+
+        let Err(TableError::Parse { .. }) = Tables::from_ron("[", CAREERS) else {
+            panic!("the birthplaces shouldn't have parsed");
+        };
+    }
+}
@@ -1,6 +1,65 @@
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::ops::RangeInclusive;
 
+thread_local! {
+    // faces to hand out instead of rolling, so scenarios and tests can fix what the dice say
+    static SCRIPTED: RefCell<Option<VecDeque<i8>>> = const { RefCell::new(None) };
+    // where random rolls come from instead of the thread's rng, so a run can be repeated
+    static SEEDED: RefCell<Option<StdRng>> = const { RefCell::new(None) };
+}
+
+/// Scripts the next dice rolled on this thread, in the order they're rolled.
+/// Each face is a single physical die - a d100 pool rolls each tens die (0-9) and then its units
+/// die (0-9), everything else rolls 1-N. Until `unscript` is called, rolling a die that wasn't
+/// scripted panics, so a scripted run can't quietly go random part way through.
+pub fn script(faces: impl IntoIterator<Item = i8>) {
+    SCRIPTED.with_borrow_mut(|scripted| scripted.get_or_insert_default().extend(faces));
+}
+
+/// Goes back to rolling randomly, returning any scripted faces that were never rolled
+pub fn unscript() -> Vec<i8> {
+    SCRIPTED
+        .take()
+        .map(|scripted| scripted.into())
+        .unwrap_or_default()
+}
+
+/// Rolls dice on this thread from `seed` until `unseed` is called, so the same seed rolls the same
+/// faces in the same order. Scripted dice still take priority.
+pub fn seed(seed: u64) {
+    SEEDED.set(Some(StdRng::seed_from_u64(seed)));
+}
+
+/// Goes back to rolling from the thread's own rng
+pub fn unseed() {
+    SEEDED.take();
+}
+
+/// Runs `f` with the dice seeded from `seed`, then puts back however they were seeded before
+pub fn seeded<T>(seed: u64, f: impl FnOnce() -> T) -> T {
+    let before = SEEDED.replace(Some(StdRng::seed_from_u64(seed)));
+    let result = f();
+    SEEDED.set(before);
+    result
+}
+
+fn roll_face(range: RangeInclusive<i8>) -> i8 {
+    SCRIPTED.with_borrow_mut(|scripted| match scripted {
+        None => SEEDED.with_borrow_mut(|seeded| match seeded {
+            Some(rng) => rng.random_range(range),
+            None => rand::rng().random_range(range),
+        }),
+        Some(faces) => match faces.pop_front() {
+            Some(face) if range.contains(&face) => face,
+            Some(face) => panic!("scripted a {face} for a die that rolls {range:?}"),
+            None => panic!("ran out of scripted dice"),
+        },
+    })
+}
+
 pub trait AsPool {
     // the dice pool may be computed dynamically, so it needs to be allocated dynamically too
     // this causes un-needed copies sometimes. TODO is there a way to only copy when needed?
@@ -104,10 +163,8 @@ pub struct D100Pool {
 impl D100Pool {
     pub fn roll(n: usize) -> Self {
         Self {
-            d100s: (0..n)
-                .map(|_| rand::rng().random_range(0..=9) * 10)
-                .collect(),
-            d10: rand::rng().random_range(0..=9),
+            d100s: (0..n).map(|_| roll_face(0..=9) * 10).collect(),
+            d10: roll_face(0..=9),
         }
     }
 }
@@ -136,7 +193,7 @@ pub struct D<const N: i8>(i8);
 
 impl<const N: i8> D<N> {
     pub fn roll() -> Self {
-        Self(rand::rng().random_range(1..=N))
+        Self(roll_face(1..=N))
     }
 }
 
@@ -222,7 +279,11 @@ impl<const N: i8> AsPool for Many<N> {
 fn roll_magic_dice(mut v: Vec<D<10>>) -> Vec<D<10>> {
     let val = d10();
     v.push(val);
-    if matches!(val.result(), 2 | 3 | 5 | 7) { roll_magic_dice(v) } else { v }
+    if matches!(val.result(), 2 | 3 | 5 | 7) {
+        roll_magic_dice(v)
+    } else {
+        v
+    }
 }
 
 #[derive(Clone)]
@@ -394,6 +455,32 @@ mod test {
         assert_eq!(Add(1, 2).render_result(), "1 + 2 = 3");
     }
 
+    #[test]
+    fn test_script() {
+        script([4, 0, 9, 3]);
+        assert_eq!(roll!(1 d 6).result(), 4);
+        assert_eq!(roll!(kh(2 d 100)).result(), 93);
+        assert!(unscript().is_empty());
+    }
+
+    #[test]
+    fn test_seed() {
+        let mut rolls = vec![];
+        for _ in 0..2 {
+            seed(7);
+            rolls.push([0; 20].map(|_| roll!(1 d 100).result()));
+        }
+        assert_eq!(rolls[0], rolls[1]);
+
+        // seeding for a while puts the seed from before back where it left off
+        seed(7);
+        let first = roll!(1 d 100).result();
+        seeded(8, || roll!(1 d 100).result());
+        let second = roll!(1 d 100).result();
+        unseed();
+        assert_eq!([first, second], rolls[0][..2]);
+    }
+
     #[test]
     fn test_subtract() {
         assert_eq!(Subtract(1, 2).result(), -1);
@@ -0,0 +1,44 @@
+use std::fmt::{Display, Formatter};
+
+/// Everything that can stop an event part way through.
+/// Events hand these to the front-end as a final `Choice::Error` rather than panicking,
+/// since most of them come from running events out of order.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ChargenError {
+    /// An event needed something that hasn't been rolled yet, eg. a career before a birthplace
+    NotRolled(&'static str),
+    /// A roll fell outside every entry on a table
+    OffTable { table: &'static str, roll: i8 },
+    /// A table entry asked for something the rules don't allow, eg. an affiliation needing a culture
+    BadTableEntry(String),
+    /// A choice was presented with nothing to pick from
+    NothingToChoose(&'static str),
+    /// The front-end answered a choice with something that wasn't on offer
+    InvalidAnswer(&'static str),
+    /// The backend doesn't support this yet
+    Unsupported(&'static str),
+    /// A front-end's `Reply` didn't fit the choice it was answering, or there was no choice to answer
+    BadReply(String),
+}
+
+impl Display for ChargenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChargenError::NotRolled(what) => write!(f, "{what} hasn't been rolled yet"),
+            ChargenError::OffTable { table, roll } => {
+                write!(f, "a roll of {roll} isn't on the {table} table")
+            }
+            ChargenError::BadTableEntry(entry) => write!(f, "bad table entry: {entry}"),
+            ChargenError::NothingToChoose(choice) => {
+                write!(f, "there was nothing to choose from for \"{choice}\"")
+            }
+            ChargenError::InvalidAnswer(choice) => {
+                write!(f, "that isn't a valid answer to \"{choice}\"")
+            }
+            ChargenError::Unsupported(what) => write!(f, "{what} isn't supported yet"),
+            ChargenError::BadReply(reason) => write!(f, "bad reply: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ChargenError {}
@@ -1,64 +1,66 @@
 pub mod birth;
+mod driver;
+pub mod scenarios;
 pub mod stages;
 mod util;
 
 use crate::backend::Backend;
-use crate::data::locations::{Culture, Faith};
 use crate::dice::DiceRoll;
 use crate::ipc::Choice;
 use crate::{maybe_roll, roll};
-pub trait Event = Iterator<Item = Choice>;
+pub use driver::{Driver, Offer, offer};
 
-pub gen fn test_pick_dice<T: Backend>(backend: &T) -> Choice {
-    let roll = maybe_roll!("test roll please ignore", backend, 1 d 10);
-    println!("got {} in the gen fn", roll.result());
+pub trait Event: Iterator<Item = Choice> + Send {}
+impl<T: Iterator<Item = Choice> + Send> Event for T {}
+
+pub fn test_pick_dice(backend: impl Backend) -> impl Event {
+    Driver::new(async move {
+        let backend = &backend;
+        let roll = maybe_roll!("test roll please ignore", backend, 1 d 10);
+        println!("got {} in the event", roll.result());
+        Ok(())
+    })
 }
 
-// test scenarios
-pub mod scenarios {
+#[cfg(test)]
+mod tests {
     use super::*;
-    use crate::Character;
-    use crate::data::careers::Affiliation;
-    use crate::data::locations::{CareerTable, Demographic, Location};
-    fn test_location() -> Option<Location> {
-        Some(Location {
-            name: "test location".to_string(),
-            culture: Culture::Varlish,
-            secondary_culture: Culture::Varlish,
-            faith: Faith::Accorder,
-            secondary_faith: Faith::Accorder,
-            demographic: Demographic::Urban,
-            career_table: CareerTable::ValiantEmpire,
-            far_afield: false,
-        })
+    use crate::{ChargenError, choose, run};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    async fn pick_number() -> Result<i32, ChargenError> {
+        Ok(choose!("Pick a number", 1, 2, 3))
     }
-    pub fn kremish_accorder(backend: &impl Backend) -> impl Event {
-        // scenario 1. You rolled a rank 3 slum folk kremish accorder
-        // you should be offered the option to convert to Gytungrug
-        *backend.get_character_mut() = Character {
-            birth_location: test_location(),
-            culture: Some(Culture::Kremish),
-            faith: Some(Faith::Accorder),
-            affiliation: Some(Affiliation::Slumfolk),
-            rank: Some(3),
-            ..Default::default()
-        };
 
-        util::change_rank(backend, 3)
+    fn double_number(doubled: Arc<AtomicI32>) -> impl Event {
+        Driver::new(async move {
+            let number = run!(pick_number());
+            doubled.store(number * 2, Ordering::Relaxed);
+            Ok(())
+        })
     }
 
-    pub fn non_kremish_accorder(backend: &impl Backend) -> impl Event {
-        // scenario 2. You rolled a rank 3 slum folk valish accorder
-        // you should NOT be offered the option to convert to Gytungrug
-        *backend.get_character_mut() = Character {
-            birth_location: test_location(),
-            culture: Some(Culture::Varlish),
-            faith: Some(Faith::Accorder),
-            affiliation: Some(Affiliation::Slumfolk),
-            rank: Some(3),
-            ..Default::default()
-        };
+    #[test]
+    fn test_sub_event_result() {
+        let doubled = Arc::new(AtomicI32::new(0));
+        for choice in double_number(doubled.clone()) {
+            match choice {
+                Choice::Selection(s) => s.chosen.set(1),
+                _ => panic!("only expected a selection"),
+            }
+        }
+        assert_eq!(doubled.load(Ordering::Relaxed), 4);
+    }
 
-        util::change_rank(backend, 3)
+    #[test]
+    fn test_out_of_order() {
+        // picking a star needs your guardians' standing, which hasn't been rolled
+        let backend = crate::BaseBackend::default();
+        let choices: Vec<_> = birth::pick_omens(&backend).collect();
+        assert!(matches!(
+            choices.as_slice(),
+            [Choice::Error(ChargenError::NotRolled(_))]
+        ));
     }
 }
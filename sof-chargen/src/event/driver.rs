@@ -0,0 +1,72 @@
+use crate::ChargenError;
+use crate::ipc::Choice;
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+
+// Events are written as async fns: presenting a choice is an await point, and sub-events are
+// plain async fns that return their result. None of this ever touches a real executor -
+// the driver below polls the event by hand whenever the front-end asks for the next choice.
+
+thread_local! {
+    // the choice an event is currently waiting on, passed from `offer` out to the driver
+    // only ever occupied for the duration of a single poll
+    static PENDING: RefCell<Option<Choice>> = const { RefCell::new(None) };
+}
+
+/// Suspends the current event until the front-end has answered `choice`.
+/// The answer itself comes back through the choice's shared cell, as it always has.
+pub fn offer(choice: Choice) -> Offer {
+    Offer(Some(choice))
+}
+
+pub struct Offer(Option<Choice>);
+
+impl Future for Offer {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<()> {
+        match self.0.take() {
+            Some(choice) => {
+                PENDING.with_borrow_mut(|pending| *pending = Some(choice));
+                Poll::Pending
+            }
+            // the driver only polls again once the choice has been answered
+            None => Poll::Ready(()),
+        }
+    }
+}
+
+/// Turns an async event body into an iterator of the choices it presents.
+/// If the body fails, its error is passed on as one last choice.
+pub struct Driver<F: Future<Output = Result<(), ChargenError>>> {
+    body: Option<Pin<Box<F>>>,
+}
+
+impl<F: Future<Output = Result<(), ChargenError>>> Driver<F> {
+    pub fn new(body: F) -> Self {
+        Self {
+            body: Some(Box::pin(body)),
+        }
+    }
+}
+
+impl<F: Future<Output = Result<(), ChargenError>>> Iterator for Driver<F> {
+    type Item = Choice;
+
+    fn next(&mut self) -> Option<Choice> {
+        let body = self.body.as_mut()?;
+        match body.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(result) => {
+                self.body = None;
+                result.err().map(Choice::Error)
+            }
+            Poll::Pending => Some(
+                PENDING
+                    .take()
+                    .expect("events may only await choices and other events"),
+            ),
+        }
+    }
+}
@@ -0,0 +1,268 @@
+// Hand-built characters for poking at events, and a harness for checking exactly what an event
+// does to them. The GUI's debug buttons use the characters, the tests below use the harness.
+use crate::backend::Backend;
+use crate::data::careers::Affiliation;
+use crate::data::locations::{CareerTable, Culture, Demographic, Faith, Location};
+use crate::event::{Driver, Event, util};
+use crate::ipc::Choice;
+use crate::{Character, ChargenError, Guardians, Subject, dice};
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex, RwLock};
+
+fn test_location() -> Option<Location> {
+    Some(Location {
+        id: "test".to_string(),
+        name: "test location".to_string(),
+        culture: Culture::Varlish,
+        secondary_culture: Culture::Varlish,
+        faith: Faith::Accorder,
+        secondary_faith: Faith::Accorder,
+        demographic: Demographic::Urban,
+        career_table: CareerTable::ValiantEmpire,
+        far_afield: false,
+    })
+}
+
+/// Scenario 1. You rolled a rank 3 slum folk kremish accorder -
+/// you should be offered the option to convert to Gytungrug
+pub fn kremish_accorder() -> Character {
+    Character {
+        birth_location: test_location(),
+        culture: Some(Culture::Kremish),
+        faith: Some(Faith::Accorder),
+        affiliation: Some(Affiliation::Slumfolk),
+        rank: Some(3),
+        guardians: Guardians {
+            culture: Some(Culture::Kremish),
+            faith: Some(Faith::Accorder),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Scenario 2. You rolled a rank 3 slum folk varlish accorder -
+/// you should NOT be offered the option to convert to Gytungrug
+pub fn non_kremish_accorder() -> Character {
+    Character {
+        birth_location: test_location(),
+        culture: Some(Culture::Varlish),
+        faith: Some(Faith::Accorder),
+        affiliation: Some(Affiliation::Slumfolk),
+        rank: Some(3),
+        guardians: Guardians {
+            culture: Some(Culture::Varlish),
+            faith: Some(Faith::Accorder),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Picks a career for the character at whatever rank they're already at
+pub fn roll_career(backend: impl Backend) -> impl Event {
+    Driver::new(async move {
+        let rank = backend
+            .get_character()
+            .rank
+            .ok_or(ChargenError::NotRolled("rank"))?;
+        util::change_rank(&backend, Subject::Character, rank).await?;
+        Ok(())
+    })
+}
+
+/// How a scenario answers a choice
+#[derive(Clone, Debug)]
+pub enum Reply {
+    Choose(usize),
+    Trait(String),
+    PickRoll(Option<i8>),
+    Answer(bool),
+}
+
+/// A character to start from, along with everything that's going to happen to them:
+/// the faces the dice land on (see `dice::script`) and the reply to each choice, in order.
+#[derive(Debug, Default)]
+pub struct Scenario {
+    pub start: Character,
+    pub dice: Vec<i8>,
+    pub replies: Vec<Reply>,
+}
+
+/// Where a scenario left the character, and how it got there
+#[derive(Debug)]
+pub struct Outcome {
+    pub character: Character,
+    // the description of every choice the event offered
+    pub offered: Vec<String>,
+    pub log: Vec<String>,
+}
+
+/// A backend that keeps its log lines rather than printing them
+#[derive(Debug, Default)]
+pub struct ScenarioBackend {
+    pub character: RwLock<Character>,
+    pub log: Mutex<Vec<String>>,
+}
+
+impl Backend for ScenarioBackend {
+    fn get_character_mut(&self) -> impl DerefMut<Target = Character> {
+        self.character.write().unwrap()
+    }
+
+    fn get_character(&self) -> impl Deref<Target = Character> {
+        self.character.read().unwrap()
+    }
+
+    fn log(&self, text: String) {
+        self.log.lock().unwrap().push(text);
+    }
+}
+
+impl Scenario {
+    /// Runs an event from the scenario's starting character.
+    /// Panics if the event fails, or strays from the script: a choice with no reply or the
+    /// wrong kind of reply, or dice and replies that were never used.
+    pub fn run<E: Event>(self, event: impl FnOnce(Arc<ScenarioBackend>) -> E) -> Outcome {
+        let backend = Arc::new(ScenarioBackend {
+            character: RwLock::new(self.start),
+            ..Default::default()
+        });
+        // the dice script is per-thread, so don't pick up anything a previous run left behind
+        dice::unscript();
+        dice::script(self.dice);
+
+        let mut replies = self.replies.into_iter();
+        let mut offered = vec![];
+        for choice in event(backend.clone()) {
+            if let Choice::Error(e) = choice {
+                panic!("the event failed: {e}");
+            }
+            offered.push(choice.description().to_string());
+            let Some(reply) = replies.next() else {
+                panic!("no reply was scripted for \"{}\"", choice.description());
+            };
+            match (choice, reply) {
+                (Choice::Selection(s), Reply::Choose(i)) => s.chosen.set(i),
+                (Choice::String(t), Reply::Trait(text)) => t.chosen.set(text),
+                (Choice::PickRoll(p), Reply::PickRoll(roll)) => p.chosen.set(roll),
+                (Choice::Question(q), Reply::Answer(a)) => q.chosen.set(a),
+                (choice, reply) => {
+                    panic!("scripted {reply:?} for \"{}\"", choice.description())
+                }
+            }
+        }
+
+        let unrolled = dice::unscript();
+        assert!(unrolled.is_empty(), "dice never rolled: {unrolled:?}");
+        let unused: Vec<_> = replies.collect();
+        assert!(unused.is_empty(), "replies never used: {unused:?}");
+
+        let backend = Arc::into_inner(backend).expect("the event is done with the backend");
+        Outcome {
+            character: backend.character.into_inner().unwrap(),
+            offered,
+            log: backend.log.into_inner().unwrap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::birth;
+
+    #[test]
+    fn test_kremish_accorder() {
+        let outcome = Scenario {
+            start: kremish_accorder(),
+            replies: vec![Reply::Choose(1), Reply::Answer(true)],
+            ..Default::default()
+        }
+        .run(roll_career);
+
+        assert_eq!(outcome.character.faith, Some(Faith::Gytungrug));
+        assert_eq!(outcome.character.careers[0].name(), "Dewisetic");
+        assert_eq!(
+            outcome.offered,
+            [
+                "Pick your career:",
+                "You need to be a Gytungrug to that that career. Do you convert?",
+            ]
+        );
+        assert_eq!(
+            outcome.log,
+            [
+                "You converted to Gytungrug.",
+                "You were apprenticed as a Dewisetic, granting you the skills of a Zealot.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_kremish_accorder_refuses() {
+        // refusing to convert sends you back to pick again
+        let outcome = Scenario {
+            start: kremish_accorder(),
+            replies: vec![Reply::Choose(1), Reply::Answer(false), Reply::Choose(0)],
+            ..Default::default()
+        }
+        .run(roll_career);
+
+        assert_eq!(outcome.character.faith, Some(Faith::Accorder));
+        assert_eq!(outcome.character.careers[0].name(), "Peddler");
+        assert_eq!(
+            outcome.log,
+            ["You were apprenticed as a Peddler, granting you the skills of a Con Artist."]
+        );
+    }
+
+    #[test]
+    fn test_non_kremish_accorder() {
+        let outcome = Scenario {
+            start: non_kremish_accorder(),
+            ..Default::default()
+        }
+        .run(roll_career);
+
+        assert_eq!(outcome.character.faith, Some(Faith::Accorder));
+        assert_eq!(outcome.character.careers[0].name(), "Peddler");
+        assert!(outcome.offered.is_empty());
+        assert_eq!(
+            outcome.log,
+            ["You were apprenticed as a Peddler, granting you the skills of a Con Artist."]
+        );
+    }
+
+    #[test]
+    fn test_roll_parents() {
+        let outcome = Scenario {
+            dice: vec![
+                1, 1, 1, 1,  // born in Duruza
+                1,  // to varlish guardians
+                1,  // who followed the Accord
+                50, // as plebeians
+                4,  // at rank 4
+            ],
+            replies: vec![Reply::Choose(0)],
+            ..Default::default()
+        }
+        .run(birth::roll_parents);
+
+        let guardians = &outcome.character.guardians;
+        assert_eq!(guardians.affiliation, Some(Affiliation::Plebeians));
+        assert_eq!(guardians.rank, Some(4));
+        assert_eq!(outcome.offered, ["Pick your guardians' career:"]);
+        assert_eq!(
+            outcome.log,
+            [
+                "Your guardians were Varlish.",
+                "Your guardians worshipped White Flame Accord.",
+                "You were born in The Imperial Capital of Duruza.",
+                "Your guardians lived life at rank 4.",
+                "Your guardians were members of the Plebeians.",
+                "Your guardians were Courtesans.",
+            ]
+        );
+    }
+}
@@ -1,8 +1,9 @@
 use crate::Backend;
 use crate::event::{Event, birth};
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Copy, Clone, Default, Eq, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
 pub enum LifeStage {
+    #[default]
     Intro,
     RollStats,
     RollParents,
@@ -13,20 +14,16 @@ pub enum LifeStage {
 
 // defines the order in which one rolls events
 impl LifeStage {
-    pub fn next(&self, backend: &'static impl Backend) -> Option<(LifeStage, Box<dyn Event>)> {
+    pub fn next<'a>(&self, backend: impl Backend + 'a) -> Option<(LifeStage, Box<dyn Event + 'a>)> {
         match self {
             LifeStage::Intro => Some((
                 LifeStage::RollStats,
                 Box::new(birth::roll_core_stats(backend)),
             )),
-            LifeStage::RollStats => {
-                // this one has no choices in, so do it before rolling parent stuff
-                birth::roll_location_of_birth(backend);
-                Some((
-                    LifeStage::RollParents,
-                    Box::new(birth::affiliation_rank_careers(backend)),
-                ))
-            }
+            LifeStage::RollStats => Some((
+                LifeStage::RollParents,
+                Box::new(birth::roll_parents(backend)),
+            )),
             LifeStage::RollParents => {
                 Some((LifeStage::RollOmens, Box::new(birth::pick_omens(backend))))
             }
@@ -38,9 +35,3 @@ impl LifeStage {
         }
     }
 }
-
-impl Default for LifeStage {
-    fn default() -> Self {
-        Self::Intro
-    }
-}
@@ -1,8 +1,9 @@
-use crate::data::careers::{CareerTableEntry, CareerTableStar, get_affiliation, get_careers};
-use crate::data::locations::Culture;
+use crate::data::careers::{
+    Affiliation, Career, CareerTableEntry, CareerTableStar, get_affiliation, get_careers,
+};
+use crate::data::locations::{Culture, Faith};
 use crate::dice::DiceRoll;
-use crate::ipc::Choice;
-use crate::{Backend, ask, choose, maybe_roll, roll};
+use crate::{Backend, ChargenError, Subject, ask, choose, maybe_roll, roll};
 use std::cmp::max;
 
 // maybe we want these to render somehow in the future?
@@ -13,40 +14,60 @@ pub(crate) fn d3() -> i8 {
     roll!(1 d 3).result()
 }
 
+// runs a sub-event to completion, passing its choices along, then evaluates to its result
 #[macro_export]
 macro_rules! run {
     ($call: expr) => {
-        for choice in $call {
-            yield choice;
-        }
+        $call.await?
     };
 }
 
-// todo i hate this i hate this i hate it so much
-pub(crate) gen fn roll_affiliation(backend: &impl Backend, mut disadvantage: usize) -> Choice {
-    let char = backend.get_character();
-    let loc = char.birth_location.clone().unwrap();
-    let culture = char.culture.unwrap();
-    let mut faith = char.faith.unwrap();
-    let mut rank = char.rank.unwrap();
+/// Where an affiliation roll left its subject
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct RolledAffiliation {
+    pub affiliation: Affiliation,
+    pub faith: Faith,
+    pub rank: i8,
+}
+
+pub(crate) async fn roll_affiliation(
+    backend: &impl Backend,
+    subject: Subject,
+    mut disadvantage: usize,
+) -> Result<RolledAffiliation, ChargenError> {
+    // don't hold onto the character across a choice, other events may want it
+    let (loc, guardians_culture, mut faith, mut rank) = {
+        let char = backend.get_character();
+        (
+            char.birth_location
+                .clone()
+                .ok_or(ChargenError::NotRolled("your birthplace"))?,
+            char.guardians
+                .culture
+                .ok_or(ChargenError::NotRolled("your guardians' culture"))?,
+            char.faith_of(subject)
+                .ok_or(ChargenError::NotRolled("faith"))?,
+            char.rank_of(subject)
+                .ok_or(ChargenError::NotRolled("rank"))?,
+        )
+    };
     let mut affiliation;
-    drop(char);
 
     loop {
         // Roll 1d100 on your birthplace’s affiliation table [...]
         // if your guardians were not members of that location’s dominant culture roll with disadvantage
         // note from lys: if you already have disadvantage from somewhere (eg table says to) it's double
         affiliation = {
-            let roll = if loc.culture == culture {
+            let roll = if loc.culture == guardians_culture {
                 // todo maybe instead of allowing them to pick a roll, it instead prompts picking
                 //   an entry from the table? Would need custom logic.
                 maybe_roll!("Roll a new affiliation", backend, kh((1+disadvantage) d 100)).result()
             } else {
                 maybe_roll!("Roll a new affiliation", backend, kh((2+disadvantage) d 100)).result()
             };
-            get_affiliation(&loc, roll)
+            get_affiliation(&loc, roll)?
         };
-        backend.set_affiliation(affiliation);
+        backend.set_affiliation(subject, affiliation);
 
         match affiliation.star(&loc) {
             CareerTableStar::None => break,
@@ -58,58 +79,74 @@ pub(crate) gen fn roll_affiliation(backend: &impl Backend, mut disadvantage: usi
                 // ask if they want to convert
                 if ask!(format!(
                     "To be a {} you must follow {}. Do you convert? \
-                                    If you don't, fall one rank and re-roll affiliation.",
+                            If you don't, fall one rank and re-roll affiliation.",
                     affiliation, f
                 )) {
                     faith = f;
-                    backend.set_faith(faith);
+                    backend.set_faith(subject, faith);
                     break;
                 } else {
                     rank = max(rank - 1, 0);
-                    backend.set_rank(rank);
+                    backend.set_rank(subject, rank);
                     // we're re-rolling so reset the disadvantage
                     disadvantage = 0;
                 }
             }
-            _ => unreachable!("Affiliations should never require a culture"),
+            star => {
+                return Err(ChargenError::BadTableEntry(format!(
+                    "{affiliation} requires {star:?}, but affiliations should never require a culture"
+                )));
+            }
         };
     }
+
+    Ok(RolledAffiliation {
+        affiliation,
+        faith,
+        rank,
+    })
 }
 
-// macro instead of a function because we would need to return a value from a gen fn
-macro_rules! handle_star {
-    ($star:ident, $career:ident, $backend:ident, $culture:ident, $faith:ident) => {
-        match $star {
-            CareerTableStar::None => break $career,
-            CareerTableStar::NeedsFaith(f) => {
-                if ask!(
+// resolves a career's star, evaluating to whether the subject may take that career
+async fn accept_star(
+    backend: &impl Backend,
+    subject: Subject,
+    career: Career,
+    star: CareerTableStar,
+    culture: Culture,
+    faith: Faith,
+) -> Result<bool, ChargenError> {
+    Ok(match star {
+        CareerTableStar::None => true,
+        CareerTableStar::NeedsFaith(f) => {
+            let convert = ask!(
+                format!("You need to be a {f:?} to that that career. Do you convert?").to_owned()
+            );
+            if convert {
+                backend.set_faith(subject, f);
+            }
+            convert
+        }
+        CareerTableStar::NeedsFaithAndCulture(f, c) => {
+            if c != culture {
+                // a choice of careers filters these out, so it's a lone career the table can't offer
+                return Err(ChargenError::BadTableEntry(format!(
+                    "{career} needs {c:?} culture, but was offered to {culture:?}"
+                )));
+            } else if f == faith {
+                true
+            } else {
+                let convert = ask!(
                     format!("You need to be a {f:?} to that that career. Do you convert?")
                         .to_owned()
-                ) {
-                    $backend.set_faith(f);
-                    break $career;
-                }
-            }
-            CareerTableStar::NeedsFaithAndCulture(f, c) => {
-                if c == $culture {
-                    if f == $faith {
-                        break $career;
-                    } else {
-                        if ask!(
-                            format!("You need to be a {f:?} to that that career. Do you convert?")
-                                .to_owned()
-                        ) {
-                            $backend.set_faith(f);
-                            break $career;
-                        }
-                    }
-                } else {
-                    // we should have filtered out for this case already
-                    println!("WARNING: Attempted to pick illegal career (wrong culture)!")
+                );
+                if convert {
+                    backend.set_faith(subject, f);
                 }
+                convert
             }
         }
-    };
+    })
 }
 
 fn is_eligible(culture: Culture, career_table_star: CareerTableStar) -> bool {
@@ -120,22 +157,36 @@ fn is_eligible(culture: Culture, career_table_star: CareerTableStar) -> bool {
     }
 }
 
-pub(crate) gen fn change_rank(backend: &impl Backend, rank: i8) -> Choice {
-    let char = backend.get_character();
-    let loc = char.birth_location.clone().unwrap();
-    let mut affiliation = char.affiliation.unwrap();
-    let culture = char.culture.unwrap();
-    let mut faith = char.faith.unwrap();
-    // drop char so we can borrow it as mut later (within handle_star)
-    drop(char);
+pub(crate) async fn change_rank(
+    backend: &impl Backend,
+    subject: Subject,
+    rank: i8,
+) -> Result<Career, ChargenError> {
+    // don't hold onto the character, we need to borrow it as mut later (within accept_star)
+    let (loc, mut affiliation, culture, mut faith) = {
+        let char = backend.get_character();
+        (
+            char.birth_location
+                .clone()
+                .ok_or(ChargenError::NotRolled("your birthplace"))?,
+            char.affiliation_of(subject)
+                .ok_or(ChargenError::NotRolled("affiliation"))?,
+            char.culture_of(subject)
+                .ok_or(ChargenError::NotRolled("culture"))?,
+            char.faith_of(subject)
+                .ok_or(ChargenError::NotRolled("faith"))?,
+        )
+    };
 
     let mut rank = rank.clamp(0, 9);
 
     let career = loop {
-        let entry = get_careers(&loc, affiliation, rank);
+        let entry = get_careers(&loc, affiliation, rank)?;
         match entry {
             CareerTableEntry::Career(career, star) => {
-                handle_star!(star, career, backend, culture, faith)
+                if run!(accept_star(backend, subject, career, star, culture, faith)) {
+                    break career;
+                }
             }
             CareerTableEntry::Careers((c1, s1), (c2, s2)) => {
                 let career = {
@@ -144,24 +195,29 @@ pub(crate) gen fn change_rank(backend: &impl Backend, rank: i8) -> Choice {
                         c2
                     } else if !is_eligible(culture, s2) {
                         c1
-                    } else if backend.get_character().parents_career.is_none() {
-                        choose!("Pick your guardians' career:", c1, c2)
                     } else {
-                        choose!("Pick your career:", c1, c2)
+                        match subject {
+                            Subject::Guardians => {
+                                choose!("Pick your guardians' career:", c1, c2)
+                            }
+                            Subject::Character => choose!("Pick your career:", c1, c2),
+                        }
                     }
                 };
 
                 let star = if career == c1 { s1 } else { s2 };
-                handle_star!(star, career, backend, culture, faith)
+                if run!(accept_star(backend, subject, career, star, culture, faith)) {
+                    break career;
+                }
             }
             CareerTableEntry::RemainAtRank(r) => {
                 rank = r;
-                backend.set_rank(rank);
+                backend.set_rank(subject, rank);
                 // rerun the loop to pick a career for that rank
             }
             CareerTableEntry::ChangeAffiliation(a) => {
                 affiliation = a;
-                backend.set_affiliation(affiliation)
+                backend.set_affiliation(subject, affiliation)
                 // rerun the loop to pick a new career for that affiliation
             }
             CareerTableEntry::RerollWithDisadvantage | CareerTableEntry::Reroll => {
@@ -171,17 +227,45 @@ pub(crate) gen fn change_rank(backend: &impl Backend, rank: i8) -> Choice {
                     0
                 };
 
-                // awkward limitation of gen fns - they can't return anything
-                // (and you can't get around that by passing a &mut arg)
-                // instead allow it to arbitrarily mutate the character and update our state after
-                run!(roll_affiliation(backend, disadvantage));
-
-                let char = backend.get_character();
-                faith = char.faith.unwrap();
-                affiliation = char.affiliation.unwrap();
-                rank = char.rank.unwrap();
+                RolledAffiliation {
+                    affiliation,
+                    faith,
+                    rank,
+                } = run!(roll_affiliation(backend, subject, disadvantage));
             }
         }
     };
-    backend.set_career(career);
+    backend.set_career(subject, career);
+    Ok(career)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::driver::Driver;
+    use crate::event::scenarios::ScenarioBackend;
+    use crate::ipc::Choice;
+
+    #[test]
+    fn test_wrong_culture() {
+        let backend = ScenarioBackend::default();
+        let career = Career::from_id("peddler").unwrap();
+        let star = CareerTableStar::NeedsFaithAndCulture(Faith::Gytungrug, Culture::Kremish);
+        let mut event = Driver::new(async {
+            run!(accept_star(
+                &backend,
+                Subject::Character,
+                career,
+                star,
+                Culture::Varlish,
+                Faith::Accorder
+            ));
+            Ok(())
+        });
+        // turned away rather than refused, which would have the same career offered forever
+        assert!(matches!(
+            event.next(),
+            Some(Choice::Error(ChargenError::BadTableEntry(_)))
+        ));
+    }
 }
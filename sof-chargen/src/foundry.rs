@@ -0,0 +1,342 @@
+// Exports characters as Foundry VTT actors, ready for "Import Data" on a blank actor. The SoF
+// system module's schema is still moving, so where everything goes is decided by a template rather
+// than the code: foundry.json in data/ is the one that's bundled, and campaigns can bring their own.
+//
+// A template has three parts: the `actor` itself, and a `career` and a `perk`, which are filled
+// in once for each career and perk the character has and put in the actor's items. Any string in
+// them can hold placeholders in double braces. A string that's just one placeholder becomes its
+// value as it is, eg. a number, null or a list; anywhere else a placeholder is written as text.
+//
+//   actor:  {{name}} {{stat:Arms}} (or any other stat) {{birthplace}} {{demographic}} {{culture}}
+//           {{faith}} {{omen}} {{affiliation}} {{rank}} {{life_stage}} {{traits}} {{biography}}
+//           (the log, as HTML paragraphs) {{items}} (the careers, then the perks)
+//   career: {{career.id}} {{career.name}} {{career.class}}
+//   perk:   {{perk.name}} {{perk.stat}} {{perk.career}} (the class of career it came from, if any)
+use crate::data::careers::{Career, CareerClass};
+use crate::data::perks::{Perk, PerkSlot};
+use crate::sheet::escape_html;
+use crate::{CORE_STATS, Character, Stat};
+use serde::Deserialize;
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+use std::sync::LazyLock;
+
+const BUNDLED_TEMPLATE: &str = include_str!("data/foundry.json");
+
+static BUNDLED: LazyLock<FoundryTemplate> =
+    LazyLock::new(|| match FoundryTemplate::from_json(BUNDLED_TEMPLATE) {
+        Ok(template) => template,
+        Err(e) => panic!("the bundled Foundry template is broken: {e}"),
+    });
+
+#[derive(Debug)]
+pub enum FoundryError {
+    Parse(serde_json::Error),
+    UnknownPlaceholder {
+        part: &'static str,
+        placeholder: String,
+    },
+}
+
+impl Display for FoundryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FoundryError::Parse(e) => write!(f, "couldn't read the template: {e}"),
+            FoundryError::UnknownPlaceholder { part, placeholder } => {
+                write!(
+                    f,
+                    "the template's {part} has no {{{{{placeholder}}}}} to fill in"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for FoundryError {}
+
+/// Where each part of a character goes in a Foundry actor
+#[derive(Clone, Debug, Deserialize)]
+pub struct FoundryTemplate {
+    actor: Value,
+    career: Value,
+    perk: Value,
+}
+
+// one perk slot that's been filled, with the stat it's under
+struct TakenPerk<'a> {
+    stat: Stat,
+    perk: &'a Perk,
+    career: Option<CareerClass>,
+}
+
+// replaces every placeholder in `template`, or gives back the first one `lookup` doesn't know
+fn fill(template: &Value, lookup: &impl Fn(&str) -> Option<Value>) -> Result<Value, String> {
+    Ok(match template {
+        Value::String(s) => {
+            let whole = s.strip_prefix("{{").and_then(|s| s.strip_suffix("}}"));
+            if let Some(key) = whole.filter(|key| !key.contains("{{")) {
+                return lookup(key.trim()).ok_or_else(|| key.trim().to_owned());
+            }
+            let mut filled = String::new();
+            let mut rest = s.as_str();
+            while let Some((before, after)) = rest.split_once("{{") {
+                let Some((key, after)) = after.split_once("}}") else {
+                    break;
+                };
+                filled.push_str(before);
+                match lookup(key.trim()).ok_or_else(|| key.trim().to_owned())? {
+                    Value::String(value) => filled.push_str(&value),
+                    Value::Null => {}
+                    value => filled.push_str(&value.to_string()),
+                }
+                rest = after;
+            }
+            filled.push_str(rest);
+            Value::String(filled)
+        }
+        Value::Array(values) => Value::Array(
+            values
+                .iter()
+                .map(|value| fill(value, lookup))
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), fill(value, lookup)?)))
+                .collect::<Result<_, String>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+fn text(thing: Option<impl ToString>) -> Value {
+    thing.map(|thing| thing.to_string()).into()
+}
+
+fn biography(log: &str) -> String {
+    log.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| format!("<p>{}</p>", escape_html(line)))
+        .collect()
+}
+
+impl FoundryTemplate {
+    /// The template that ships with the crate
+    pub fn bundled() -> &'static FoundryTemplate {
+        &BUNDLED
+    }
+
+    /// Reads a template, and checks there's nothing in it that can't be filled in
+    pub fn from_json(text: &str) -> Result<Self, FoundryError> {
+        let template: FoundryTemplate = serde_json::from_str(text).map_err(FoundryError::Parse)?;
+
+        // placeholders are known whether or not there's anything to fill them with, so filling in
+        // a blank character, and any career and perk, finds every one that isn't
+        let unknown =
+            |part| move |placeholder| FoundryError::UnknownPlaceholder { part, placeholder };
+        let character = Character::default();
+        fill(&template.actor, &|key| {
+            template.actor_value(&character, "", key)
+        })
+        .map_err(unknown("actor"))?;
+        let career = Career::all().next().unwrap();
+        fill(&template.career, &|key| Self::career_value(career, key))
+            .map_err(unknown("career"))?;
+        let perk = TakenPerk {
+            stat: Stat::Arms,
+            perk: &Perk::Swimmer,
+            career: None,
+        };
+        fill(&template.perk, &|key| Self::perk_value(&perk, key)).map_err(unknown("perk"))?;
+        Ok(template)
+    }
+
+    /// The character as a Foundry actor, with `log` (as kept by a `LogBackend`) as their biography
+    pub fn actor(&self, character: &Character, log: &str) -> Value {
+        fill(&self.actor, &|key| self.actor_value(character, log, key))
+            .expect("placeholders are checked when the template's read")
+    }
+
+    fn items(&self, character: &Character) -> Value {
+        let careers = character.careers.iter().map(|&career| {
+            fill(&self.career, &|key| Self::career_value(career, key))
+                .expect("placeholders are checked when the template's read")
+        });
+        let perks = CORE_STATS.iter().flat_map(|&stat| {
+            character
+                .perks
+                .slots(stat)
+                .iter()
+                .filter_map(move |slot| match slot {
+                    PerkSlot::Perk(perk, career) => Some(TakenPerk {
+                        stat,
+                        perk,
+                        career: *career,
+                    }),
+                    _ => None,
+                })
+        });
+        let perks = perks.map(|perk| {
+            fill(&self.perk, &|key| Self::perk_value(&perk, key))
+                .expect("placeholders are checked when the template's read")
+        });
+        careers.chain(perks).collect()
+    }
+
+    fn actor_value(&self, character: &Character, log: &str, key: &str) -> Option<Value> {
+        if let Some(name) = key.strip_prefix("stat:") {
+            return character
+                .stats
+                .iter()
+                .find(|(stat, _)| stat.to_string() == name)
+                .map(|(_, &value)| value.into());
+        }
+        let location = character.birth_location.as_ref();
+        Some(match key {
+            "name" => character.name.clone().into(),
+            "birthplace" => text(location.map(|location| &location.name)),
+            "demographic" => text(location.map(|location| format!("{:?}", location.demographic))),
+            "culture" => text(character.culture),
+            "faith" => text(character.faith),
+            "omen" => text(character.omen),
+            "affiliation" => text(character.affiliation),
+            "rank" => character.rank.into(),
+            "life_stage" => format!("{:?}", character.life_stage).into(),
+            "traits" => character.traits.clone().into(),
+            "biography" => biography(log).into(),
+            "items" => self.items(character),
+            _ => return None,
+        })
+    }
+
+    fn career_value(career: Career, key: &str) -> Option<Value> {
+        Some(match key {
+            "career.id" => career.id().into(),
+            "career.name" => career.name().into(),
+            "career.class" => career.class().to_string().into(),
+            _ => return None,
+        })
+    }
+
+    fn perk_value(perk: &TakenPerk, key: &str) -> Option<Value> {
+        Some(match key {
+            "perk.name" => perk.perk.to_string().into(),
+            "perk.stat" => perk.stat.to_string().into(),
+            "perk.career" => text(perk.career),
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BirthOmen;
+    use crate::data::careers::Affiliation;
+    use crate::data::locations::{Culture, Demographic, Faith};
+    use std::path::{Path, PathBuf};
+
+    fn golden(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("testdata/foundry")
+            .join(name)
+    }
+
+    // compares against a file in testdata/foundry, or rewrites it with UPDATE_GOLDEN=1
+    fn assert_golden(actor: &Value, name: &str) {
+        let actor = serde_json::to_string_pretty(actor).unwrap() + "\n";
+        let path = golden(name);
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::write(&path, &actor).unwrap();
+        }
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap_or_default(),
+            actor,
+            "{name} is out of date; run with UPDATE_GOLDEN=1 to regenerate it"
+        );
+    }
+
+    fn character() -> Character {
+        let mut character = Character {
+            name: "Ash of Duruza".to_owned(),
+            birth_location: crate::data::tables::bundled()
+                .location_by_id("duruza", Demographic::Urban),
+            culture: Some(Culture::Myrsc(Faith::Grevite)),
+            faith: Some(Faith::Grevite),
+            omen: Some(BirthOmen::ShootingStar),
+            affiliation: Some(Affiliation::Watch),
+            rank: Some(3),
+            careers: vec![Career::from_id("peddler").unwrap()],
+            traits: vec!["Stubborn".to_owned()],
+            ..Default::default()
+        };
+        for (i, stat) in CORE_STATS.iter().enumerate() {
+            character.stats[*stat] = Some(30 + 5 * i as i8);
+            for subskill in stat.subskills() {
+                character.stats[subskill] = Some(35 + 5 * i as i8);
+            }
+        }
+        character.stats[Stat::Luck] = Some(12);
+        character
+            .perks
+            .insert_perk(Stat::Arms, Perk::ArmourTraining, None)
+            .unwrap();
+        character
+            .perks
+            .insert_perk(
+                Stat::Face,
+                Perk::Bard,
+                Some(Career::from_id("peddler").unwrap().class()),
+            )
+            .unwrap();
+        character
+    }
+
+    const LOG: &str =
+        "\nYou were born in The Imperial Capital of Duruza.\n\nYou became a <Peddler>.";
+
+    #[test]
+    fn test_bundled() {
+        assert_golden(
+            &FoundryTemplate::bundled().actor(&character(), LOG),
+            "actor.json",
+        );
+        assert_golden(
+            &FoundryTemplate::bundled().actor(&Character::default(), ""),
+            "empty.json",
+        );
+    }
+
+    #[test]
+    fn test_custom_template() {
+        let template = std::fs::read_to_string(golden("custom-template.json")).unwrap();
+        let template = FoundryTemplate::from_json(&template).unwrap();
+        assert_golden(&template.actor(&character(), LOG), "custom.json");
+    }
+
+    #[test]
+    fn test_bad_template() {
+        let error = FoundryTemplate::from_json(
+            r#"{"actor": {"name": "{{nmae}}"}, "career": {}, "perk": {}}"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the template's actor has no {{nmae}} to fill in"
+        );
+        let error = FoundryTemplate::from_json(
+            r#"{"actor": {}, "career": {"name": "{{perk.name}}"}, "perk": {}}"#,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            FoundryError::UnknownPlaceholder { part: "career", .. }
+        ));
+        assert!(matches!(
+            FoundryTemplate::from_json(r#"{"actor": {}}"#),
+            Err(FoundryError::Parse(_))
+        ));
+    }
+}
@@ -1,7 +1,7 @@
+use crate::ChargenError;
 use crate::dice::DiceRoll;
-use std::cell::Cell;
 use std::fmt::Display;
-use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 // An API for implementing the axiom of choice by presenting a vector of options to a user
 // - can select a thing from a vector of things
 // - those things need not be copy/clone
@@ -24,19 +24,50 @@ impl Choosable {
     }
 }
 
+/// Where a front-end writes its answer to a choice.
+/// Shared between the choice and the event waiting on it, which may be on another thread.
+pub struct Answer<T>(Arc<Mutex<T>>);
+
+impl<T> Answer<T> {
+    pub fn new(value: T) -> Self {
+        Self(Arc::new(Mutex::new(value)))
+    }
+    pub fn set(&self, value: T) {
+        *self.0.lock().unwrap() = value;
+    }
+    pub fn get(&self) -> T
+    where
+        T: Copy,
+    {
+        *self.0.lock().unwrap()
+    }
+    pub fn take(&self) -> T
+    where
+        T: Default,
+    {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+impl<T> Clone for Answer<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
 pub struct Selection {
     pub description: &'static str,
     // the main downside of the trait is that all the things are moved into an rc
     // it may be possible to get around this by making choosable a struct
     pub options: Vec<Choosable>,
-    // this RC is how we signal home
+    // this is how we signal home
     // Rust doesn't support passing additional arguments into generator expressions like Python
-    pub chosen: Rc<Cell<usize>>,
+    pub chosen: Answer<usize>,
 }
 
 pub struct TraitChoice {
     pub description: &'static str,
-    pub chosen: Rc<Cell<String>>,
+    pub chosen: Answer<String>,
 }
 
 // prophetic stars lets you pick a roll two times - this represents that choice
@@ -44,13 +75,13 @@ pub struct TraitChoice {
 // for table rolls, we can probably just use selection above with a "pick randomly" option added
 pub struct PickRoll {
     pub description: &'static str,
-    pub roll: Box<dyn DiceRoll>,
-    pub chosen: Rc<Cell<Option<i8>>>,
+    pub roll: Box<dyn DiceRoll + Send>,
+    pub chosen: Answer<Option<i8>>,
 }
 
 pub struct Question {
     pub description: String,
-    pub chosen: Rc<Cell<bool>>,
+    pub chosen: Answer<bool>,
 }
 
 pub enum Choice {
@@ -58,6 +89,8 @@ pub enum Choice {
     String(TraitChoice),
     PickRoll(PickRoll),
     Question(Question),
+    // not really a choice - the event gave up, and this is the last thing it'll send
+    Error(ChargenError),
 }
 
 impl From<Selection> for Choice {
@@ -83,6 +116,124 @@ impl Choice {
             Choice::String(t) => t.description,
             Choice::PickRoll(p) => p.description,
             Choice::Question(q) => &q.description,
+            Choice::Error(_) => "Something went wrong",
+        }
+    }
+
+    /// The choice as plain data, for a front-end on the other side of a wire
+    pub fn view(&self) -> ChoiceView {
+        let description = self.description().to_owned();
+        match self {
+            Choice::Selection(s) => ChoiceView::Selection {
+                description,
+                options: s.options.iter().map(|o| o.description.clone()).collect(),
+            },
+            Choice::String(_) => ChoiceView::String { description },
+            Choice::PickRoll(p) => ChoiceView::PickRoll {
+                description,
+                roll: p.roll.render(),
+                min: *p.roll.range().start(),
+                max: *p.roll.range().end(),
+            },
+            Choice::Question(_) => ChoiceView::Question { description },
+            Choice::Error(e) => ChoiceView::Error {
+                description,
+                error: e.to_string(),
+            },
+        }
+    }
+
+    /// Answers the choice from plain data, checking the reply is one it could have been given
+    pub fn reply(&self, reply: Reply) -> Result<(), ChargenError> {
+        match (self, reply) {
+            (Choice::Selection(s), Reply::Selection(i)) if i < s.options.len() => s.chosen.set(i),
+            (Choice::Selection(s), Reply::Selection(i)) => {
+                return Err(ChargenError::BadReply(format!(
+                    "there's no option {i}, only {}",
+                    s.options.len()
+                )));
+            }
+            (Choice::String(t), Reply::String(text)) => t.chosen.set(text),
+            (Choice::PickRoll(p), Reply::PickRoll(Some(pick)))
+                if !p.roll.range().contains(&pick) =>
+            {
+                let range = p.roll.range();
+                return Err(ChargenError::BadReply(format!(
+                    "{pick} isn't between {} and {}",
+                    range.start(),
+                    range.end()
+                )));
+            }
+            (Choice::PickRoll(p), Reply::PickRoll(pick)) => p.chosen.set(pick),
+            (Choice::Question(q), Reply::Question(answer)) => q.chosen.set(answer),
+            (Choice::Error(_), Reply::Dismiss) => {}
+            (choice, reply) => {
+                return Err(ChargenError::BadReply(format!(
+                    "{} doesn't answer {}",
+                    reply.kind(),
+                    choice.view().kind()
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A `Choice` as plain data: what's being asked, and what the answers could be
+#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChoiceView {
+    /// Answered by the index of one of the options
+    Selection {
+        description: String,
+        options: Vec<String>,
+    },
+    /// Answered with some text
+    String { description: String },
+    /// Answered with a number from `min` to `max`, or none to roll as normal
+    PickRoll {
+        description: String,
+        roll: String,
+        min: i8,
+        max: i8,
+    },
+    /// Answered yes or no
+    Question { description: String },
+    /// Not really a choice: the event gave up, and this only needs dismissing
+    Error { description: String, error: String },
+}
+
+impl ChoiceView {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ChoiceView::Selection { .. } => "a selection",
+            ChoiceView::String { .. } => "a string",
+            ChoiceView::PickRoll { .. } => "a picked roll",
+            ChoiceView::Question { .. } => "a question",
+            ChoiceView::Error { .. } => "an error",
+        }
+    }
+}
+
+/// An answer to a `ChoiceView`, of the same kind
+#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reply {
+    Selection(usize),
+    String(String),
+    PickRoll(Option<i8>),
+    Question(bool),
+    Dismiss,
+}
+
+impl Reply {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Reply::Selection(_) => "a selection",
+            Reply::String(_) => "a string",
+            Reply::PickRoll(_) => "a picked roll",
+            Reply::Question(_) => "a yes or no",
+            Reply::Dismiss => "dismissing",
         }
     }
 }
@@ -93,26 +244,38 @@ macro_rules! choose {
         {
             let mut orig = vec![$($x),*];
             let options = orig.iter().map(|x| $crate::ipc::Choosable::from(x)).collect();
-            let chosen = std::rc::Rc::from(std::cell::Cell::new(0));
-            yield $crate::ipc::Selection {description: ($descr), options, chosen: chosen.clone()}.into();
+            let chosen = $crate::ipc::Answer::new(0);
+            $crate::event::offer($crate::ipc::Selection {description: ($descr), options, chosen: chosen.clone()}.into()).await;
+            if chosen.get() >= orig.len() {
+                return Err($crate::ChargenError::InvalidAnswer($descr).into());
+            }
             orig.remove(chosen.get())
         }
     };
 }
 #[macro_export]
 macro_rules! choose_vec {
-    ($descr: literal, $x: ident) => {{
+    ($descr: expr, $x: ident) => {{
+        if $x.is_empty() {
+            return Err($crate::ChargenError::NothingToChoose($descr).into());
+        }
         let options = $x.iter().map(|x| $crate::ipc::Choosable::from(x)).collect();
-        let chosen = std::rc::Rc::from(std::cell::Cell::new(0));
-        yield $crate::ipc::Selection {
-            description: ($descr),
-            options,
-            chosen: chosen.clone(),
+        let chosen = $crate::ipc::Answer::new(0);
+        $crate::event::offer(
+            $crate::ipc::Selection {
+                description: ($descr),
+                options,
+                chosen: chosen.clone(),
+            }
+            .into(),
+        )
+        .await;
+        if chosen.get() >= $x.len() {
+            return Err($crate::ChargenError::InvalidAnswer($descr).into());
         }
-        .into();
         $x.remove(chosen.get())
     }};
-    ($descr: literal, $x: expr) => {{
+    ($descr: expr, $x: expr) => {{
         // maybe we got passed an iter - if so, consume it into a vector
         let mut orig: Vec<_> = ($x).into_iter().collect();
         choose_vec!($descr, orig)
@@ -120,7 +283,7 @@ macro_rules! choose_vec {
     // consume causes the macro to take ownership of the vector
     // this allows it to be rebound as mutable even if it was originally immutable
     // see tests::test_ints for an example of where this is needed
-    (consume $descr: literal, $x: expr) => {{
+    (consume $descr: expr, $x: expr) => {{
         // put it in parentheses to ensure that it's always an expression and not an ident
         choose_vec!($descr, ($x))
     }};
@@ -129,12 +292,15 @@ macro_rules! choose_vec {
 #[macro_export]
 macro_rules! input_trait {
     ($description: literal) => {{
-        let chosen = std::rc::Rc::from(std::cell::Cell::new(String::new()));
-        yield $crate::ipc::TraitChoice {
-            description: ($description),
-            chosen: chosen.clone(),
-        }
-        .into();
+        let chosen = $crate::ipc::Answer::new(String::new());
+        $crate::event::offer(
+            $crate::ipc::TraitChoice {
+                description: ($description),
+                chosen: chosen.clone(),
+            }
+            .into(),
+        )
+        .await;
         chosen.take()
     }};
 }
@@ -143,13 +309,16 @@ macro_rules! input_trait {
 macro_rules! pick_roll {
     ($description: literal, $roll: expr) => {{
         let roll = $roll;
-        let chosen = std::rc::Rc::from(std::cell::Cell::new(None));
-        yield $crate::ipc::PickRoll {
-            description: $description,
-            roll: Box::new(roll),
-            chosen: chosen.clone(),
-        }
-        .into();
+        let chosen = $crate::ipc::Answer::new(None);
+        $crate::event::offer(
+            $crate::ipc::PickRoll {
+                description: $description,
+                roll: Box::new(roll),
+                chosen: chosen.clone(),
+            }
+            .into(),
+        )
+        .await;
         chosen.take()
     }};
 }
@@ -164,6 +333,9 @@ macro_rules! maybe_roll {
             Some($crate::character::BirthOmen::PropheticSigns(charges)) if charges != 0 => {
                 match $crate::pick_roll!($description, roll.clone()) {
                     None => $crate::dice::PickedRoll(roll.result(), roll),
+                    Some(i) if !roll.range().contains(&i) => {
+                        return Err($crate::ChargenError::InvalidAnswer($description).into());
+                    }
                     Some(i) => {
                         $backend.set_omen($crate::character::BirthOmen::PropheticSigns(charges-1));
                         $crate::dice::PickedRoll(i, roll)
@@ -178,12 +350,12 @@ macro_rules! maybe_roll {
 #[macro_export]
 macro_rules! ask {
     ($description: expr) => {{
-        let answer = std::rc::Rc::new(core::cell::Cell::new(false));
+        let answer = $crate::ipc::Answer::new(false);
         let question = $crate::ipc::Choice::Question($crate::ipc::Question {
             description: $description,
             chosen: answer.clone(),
         });
-        yield question;
+        $crate::event::offer(question).await;
         answer.get()
     }};
 }
@@ -191,6 +363,7 @@ macro_rules! ask {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::event::Driver;
     use std::fmt::Formatter;
 
     fn run_test(mut iter: impl Iterator<Item = Choice>) {
@@ -201,20 +374,22 @@ mod tests {
                 Some(Choice::Selection(s)) => s.chosen.set(0),
                 Some(Choice::PickRoll(p)) => p.chosen.set(Some(*p.roll.range().end())),
                 Some(Choice::Question(q)) => q.chosen.set(true),
+                Some(Choice::Error(e)) => panic!("{e}"),
             }
         }
     }
 
-    gen fn test_ints() -> Choice {
+    async fn test_ints() -> Result<(), ChargenError> {
         let vec = vec![1, 2, 3];
         let choice = choose_vec!(consume "something", vec);
         assert_eq!(choice, 1);
 
         let choice = choose!("something", 1, 2, 3);
         assert_eq!(choice, 1);
+        Ok(())
     }
 
-    gen fn test_no_copy() -> Choice {
+    async fn test_no_copy() -> Result<(), ChargenError> {
         #[derive(Debug, Eq, PartialEq)]
         struct Foo(i32);
         impl Display for Foo {
@@ -230,11 +405,73 @@ mod tests {
 
         let choice: Foo = choose!("something", Foo(1), Foo(2), Foo(3));
         assert_eq!(choice, Foo(1));
+        Ok(())
+    }
+
+    async fn test_bad_answer() -> Result<(), ChargenError> {
+        choose!("something", 1, 2, 3);
+        Ok(())
+    }
+
+    async fn test_nothing_to_choose() -> Result<(), ChargenError> {
+        choose_vec!("something", Vec::<i32>::new());
+        Ok(())
     }
 
     #[test]
     fn test_simple_choices() {
-        run_test(test_ints());
-        run_test(test_no_copy());
+        run_test(Driver::new(test_ints()));
+        run_test(Driver::new(test_no_copy()));
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut event = Driver::new(test_bad_answer());
+        match event.next() {
+            Some(Choice::Selection(s)) => s.chosen.set(3),
+            _ => panic!("expected a selection"),
+        }
+        assert!(matches!(
+            event.next(),
+            Some(Choice::Error(ChargenError::InvalidAnswer("something")))
+        ));
+        assert!(event.next().is_none());
+
+        let mut event = Driver::new(test_nothing_to_choose());
+        assert!(matches!(
+            event.next(),
+            Some(Choice::Error(ChargenError::NothingToChoose("something")))
+        ));
+    }
+
+    #[test]
+    fn test_replies() {
+        let mut event = Driver::new(test_ints());
+        let choice = event.next().unwrap();
+        assert_eq!(
+            choice.view(),
+            ChoiceView::Selection {
+                description: String::from("something"),
+                options: vec![String::from("1"), String::from("2"), String::from("3")],
+            }
+        );
+        assert!(choice.reply(Reply::Selection(3)).is_err());
+        assert_eq!(
+            choice.reply(Reply::Question(true)),
+            Err(ChargenError::BadReply(String::from(
+                "a yes or no doesn't answer a selection"
+            )))
+        );
+        assert!(choice.reply(Reply::Selection(0)).is_ok());
+        assert!(matches!(event.next(), Some(Choice::Selection(_))));
+
+        // they're meant to go over the wire
+        let json = r#"{"kind":"question","description":"Convert?"}"#;
+        let view: ChoiceView = serde_json::from_str(json).unwrap();
+        assert_eq!(serde_json::to_string(&view).unwrap(), json);
+        assert_eq!(
+            serde_json::from_str::<Reply>(r#"{"pick_roll":null}"#).unwrap(),
+            Reply::PickRoll(None)
+        );
     }
 }
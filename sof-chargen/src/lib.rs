@@ -1,17 +1,22 @@
-#![feature(gen_blocks)]
-#![feature(trait_alias)]
-
 #[macro_use]
 extern crate enum_map;
 
-pub use backend::Backend;
-pub use character::{BirthOmen, CORE_STATS, Character, Stat};
+pub use backend::{Backend, BaseBackend, LogBackend};
+pub use character::{BirthOmen, CORE_STATS, Character, Guardians, Stat, Subject};
+pub use error::ChargenError;
 
 mod backend;
 mod character;
 pub mod dice;
+mod error;
 pub mod event;
+pub mod foundry;
 pub mod ipc;
+pub mod save;
+pub mod session;
+pub mod share;
+pub mod sheet;
+pub mod sim;
 
 pub mod data;
 
@@ -34,7 +39,7 @@ pub mod data;
  - types of transition
  - star signs?
 
- load from files:
+ load from files (birthplaces, careers and career tables are in the data folder so far):
  - events
  - careers
  - career tables
@@ -0,0 +1,187 @@
+// Saves are the backend as RON, in an envelope that says which layout of it is inside. The layout
+// changes with most alphas, so every older one is kept here along with a migration to the next,
+// and a save from any previous release loads into the current one instead of being thrown away.
+//
+// Saves from before the envelope are just the backend, and are told apart by which layout fits.
+//
+// When `Character` or `LogBackend` change in a way that stops old saves parsing: move the old
+// layout into a new module here, with a migration up to the current one, bump `SAVE_VERSION`, and
+// add a fixture of the old format to testdata/saves.
+mod v1;
+mod v2;
+
+use crate::LogBackend;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// The layout saves are written in
+pub const SAVE_VERSION: u32 = 3;
+
+#[derive(Debug)]
+pub enum SaveError {
+    /// The save doesn't fit the layout its version says it has, or any layout at all
+    Parse(ron::error::SpannedError),
+    /// The save is from a newer release than this one
+    TooNew(u32),
+    /// The save parsed, but refers to something that's since been taken out, eg. a career
+    Migration { version: u32, reason: String },
+}
+
+impl Display for SaveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveError::Parse(e) => write!(f, "{e}"),
+            SaveError::TooNew(version) => write!(
+                f,
+                "the save is version {version}, but this release only reads up to {SAVE_VERSION}"
+            ),
+            SaveError::Migration { version, reason } => {
+                write!(
+                    f,
+                    "couldn't bring a version {version} save up to date: {reason}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl From<ron::error::SpannedError> for SaveError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        SaveError::Parse(e)
+    }
+}
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    version: u32,
+    backend: &'a LogBackend,
+}
+
+// everything but the version is skipped over, so it can be read before knowing the layout
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+#[derive(Deserialize)]
+struct Versioned<T> {
+    backend: T,
+}
+
+/// Writes a save in the current layout
+pub fn to_string(backend: &LogBackend) -> String {
+    ron::to_string(&Envelope {
+        version: SAVE_VERSION,
+        backend,
+    })
+    .expect("failed to serialize backend!")
+}
+
+/// Reads a save from this release or any before it
+pub fn from_str(save: &str) -> Result<LogBackend, SaveError> {
+    match ron::from_str::<Header>(save) {
+        Ok(Header { version: 1 }) => from_v1(ron::from_str::<Versioned<_>>(save)?.backend),
+        Ok(Header { version: 2 }) => from_v2(2, ron::from_str::<Versioned<_>>(save)?.backend),
+        Ok(Header { version: 3 }) => Ok(ron::from_str::<Versioned<_>>(save)?.backend),
+        Ok(Header { version }) => Err(SaveError::TooNew(version)),
+        Err(_) => from_bare(save),
+    }
+}
+
+// from before the envelope, when a save was just the backend. Layouts before this one don't fit
+// its careers or birthplace, and the older two don't fit each other's fields, so at most one fits,
+// bar very early saves that have neither and load the same whichever they're taken as.
+fn from_bare(save: &str) -> Result<LogBackend, SaveError> {
+    let current = match ron::from_str(save) {
+        Ok(backend) => return Ok(backend),
+        Err(e) => e,
+    };
+    if let Ok(backend) = ron::from_str(save) {
+        return from_v2(2, backend);
+    }
+    if let Ok(backend) = ron::from_str(save) {
+        return from_v1(backend);
+    }
+    // the current layout's complaint is the one most likely to help
+    Err(SaveError::Parse(current))
+}
+
+fn from_v1(backend: v1::Backend) -> Result<LogBackend, SaveError> {
+    from_v2(1, backend.into())
+}
+
+fn from_v2(version: u32, backend: v2::Backend) -> Result<LogBackend, SaveError> {
+    backend
+        .try_into()
+        .map_err(|reason| SaveError::Migration { version, reason })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Backend;
+    use std::path::Path;
+
+    fn fixture(name: &str) -> String {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("testdata/saves")
+            .join(name);
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    // the fixtures are all the same character, saved by the release each layout comes from
+    fn expected() -> String {
+        ron::to_string(&ron::from_str::<LogBackend>(&fixture("v3.sof")).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_bare_saves() {
+        for name in ["v1.sof", "v2.sof", "v3.sof"] {
+            let backend = from_str(&fixture(name)).unwrap_or_else(|e| panic!("{name}: {e}"));
+            assert_eq!(ron::to_string(&backend).unwrap(), expected(), "{name}");
+        }
+
+        let v1 = from_str(&fixture("v1.sof")).unwrap();
+        let character = v1.get_character();
+        assert_eq!(character.guardians.career.unwrap().name(), "Bodyguard");
+        assert_eq!(character.careers[0].id(), "peddler");
+        assert_eq!(character.birth_location.as_ref().unwrap().id, "duruza");
+    }
+
+    #[test]
+    fn test_envelope() {
+        let backend = from_str(&fixture("v3.sof")).unwrap();
+        let save = to_string(&backend);
+        assert!(save.starts_with("(version:3,backend:(character:"));
+        assert_eq!(to_string(&from_str(&save).unwrap()), save);
+
+        // an older layout in an envelope is migrated just the same
+        let v2 = format!("(version:2,backend:{})", fixture("v2.sof"));
+        assert_eq!(ron::to_string(&from_str(&v2).unwrap()).unwrap(), expected());
+    }
+
+    #[test]
+    fn test_bad_saves() {
+        assert!(matches!(from_str("nonsense"), Err(SaveError::Parse(_))));
+        assert!(matches!(
+            from_str("(version:99,backend:())"),
+            Err(SaveError::TooNew(99))
+        ));
+
+        let gone = fixture("v1.sof").replace("\"Peddler\"", "\"Pedlar\"");
+        let error = from_str(&gone).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "couldn't bring a version 1 save up to date: there's no career called \"Pedlar\" any more"
+        );
+
+        // a version 1 save with guardians added isn't any layout, rather than half of one
+        let mixed = fixture("v1.sof").replace(
+            "life_stage:Events",
+            "life_stage:Events,guardians:(affiliation:None,rank:None,career:None,culture:None,faith:None)",
+        );
+        assert!(from_str(&mixed).is_err());
+    }
+}
@@ -0,0 +1,69 @@
+// How saves were laid out before guardians had their own standing. The affiliation, rank, culture
+// and faith rolled at birth were written straight onto the character, who inherited all of them,
+// with just the parents' career kept apart.
+use super::v2;
+use crate::data::careers::Affiliation;
+use crate::data::locations::{Culture, Faith};
+use crate::data::perks::Perks;
+use crate::event::stages::LifeStage;
+use crate::{BirthOmen, Stat};
+use enum_map::EnumMap;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct Character {
+    stats: EnumMap<Stat, Option<i8>>,
+    name: String,
+    traits: Vec<String>,
+    omen: Option<BirthOmen>,
+    perks: Perks,
+    birth_location: Option<v2::Location>,
+    culture: Option<Culture>,
+    faith: Option<Faith>,
+    affiliation: Option<Affiliation>,
+    parents_career: Option<v2::Career>,
+    careers: Vec<v2::Career>,
+    rank: Option<i8>,
+    life_stage: LifeStage,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct Backend {
+    character: Character,
+    log: String,
+}
+
+impl From<Backend> for v2::Backend {
+    fn from(
+        Backend {
+            character: old,
+            log,
+        }: Backend,
+    ) -> Self {
+        let guardians = v2::Guardians {
+            affiliation: old.affiliation,
+            rank: old.rank,
+            career: old.parents_career,
+            culture: old.culture,
+            faith: old.faith,
+        };
+        let character = v2::Character {
+            stats: old.stats,
+            name: old.name,
+            traits: old.traits,
+            omen: old.omen,
+            perks: old.perks,
+            birth_location: old.birth_location,
+            culture: old.culture,
+            faith: old.faith,
+            affiliation: old.affiliation,
+            careers: old.careers,
+            rank: old.rank,
+            life_stage: old.life_stage,
+            guardians,
+        };
+        v2::Backend { character, log }
+    }
+}
@@ -0,0 +1,129 @@
+// How saves were laid out from when guardians got their own standing until careers and birthplaces
+// were saved by id: both were written out whole, so they're found again by name.
+use crate::data::careers::Affiliation;
+use crate::data::locations::{Culture, Demographic, Faith};
+use crate::data::perks::Perks;
+use crate::data::tables::bundled;
+use crate::event::stages::LifeStage;
+use crate::{BirthOmen, Guardians as NewGuardians, LogBackend, Stat};
+use enum_map::EnumMap;
+use serde::Deserialize;
+
+// its class was saved too, but the name's enough to find it
+#[derive(Deserialize)]
+pub(super) struct Career {
+    pub(super) name: String,
+}
+
+impl TryFrom<Career> for crate::data::careers::Career {
+    type Error = String;
+
+    fn try_from(career: Career) -> Result<Self, Self::Error> {
+        Self::all()
+            .find(|known| known.name() == career.name)
+            .ok_or_else(|| format!("there's no career called \"{}\" any more", career.name))
+    }
+}
+
+// as is everything else about the birthplace, which the tables hold now
+#[derive(Deserialize)]
+pub(super) struct Location {
+    pub(super) name: String,
+    pub(super) demographic: Demographic,
+}
+
+impl TryFrom<Location> for crate::data::locations::Location {
+    type Error = String;
+
+    fn try_from(location: Location) -> Result<Self, Self::Error> {
+        let tables = bundled();
+        tables
+            .birthplaces()
+            .find(|(_, birthplace)| birthplace.name == location.name)
+            .and_then(|(_, birthplace)| tables.location_by_id(&birthplace.id, location.demographic))
+            .ok_or_else(|| {
+                format!(
+                    "there's no birthplace called \"{}\" any more",
+                    location.name
+                )
+            })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct Guardians {
+    pub(super) affiliation: Option<Affiliation>,
+    pub(super) rank: Option<i8>,
+    pub(super) career: Option<Career>,
+    pub(super) culture: Option<Culture>,
+    pub(super) faith: Option<Faith>,
+}
+
+// the fields are checked strictly so that a version 1 save, which has the same ones bar the
+// guardians, can't be mistaken for one of these
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct Character {
+    pub(super) stats: EnumMap<Stat, Option<i8>>,
+    pub(super) name: String,
+    pub(super) traits: Vec<String>,
+    pub(super) omen: Option<BirthOmen>,
+    pub(super) perks: Perks,
+    pub(super) birth_location: Option<Location>,
+    pub(super) culture: Option<Culture>,
+    pub(super) faith: Option<Faith>,
+    pub(super) affiliation: Option<Affiliation>,
+    pub(super) careers: Vec<Career>,
+    pub(super) rank: Option<i8>,
+    pub(super) life_stage: LifeStage,
+    pub(super) guardians: Guardians,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct Backend {
+    pub(super) character: Character,
+    pub(super) log: String,
+}
+
+impl TryFrom<Backend> for LogBackend {
+    type Error = String;
+
+    fn try_from(
+        Backend {
+            character: old,
+            log,
+        }: Backend,
+    ) -> Result<Self, Self::Error> {
+        let character = crate::Character {
+            stats: old.stats,
+            name: old.name,
+            traits: old.traits,
+            omen: old.omen,
+            perks: old.perks,
+            birth_location: old.birth_location.map(TryInto::try_into).transpose()?,
+            culture: old.culture,
+            faith: old.faith,
+            affiliation: old.affiliation,
+            careers: old
+                .careers
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+            rank: old.rank,
+            life_stage: old.life_stage,
+            guardians: NewGuardians {
+                affiliation: old.guardians.affiliation,
+                rank: old.guardians.rank,
+                career: old.guardians.career.map(TryInto::try_into).transpose()?,
+                culture: old.guardians.culture,
+                faith: old.guardians.faith,
+            },
+        };
+        Ok(LogBackend {
+            character: character.into(),
+            log: log.into(),
+        })
+    }
+}
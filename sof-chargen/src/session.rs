@@ -0,0 +1,210 @@
+use crate::event::Event;
+use crate::ipc::{Choice, ChoiceView, Reply};
+use crate::save::{self, SaveError};
+use crate::{Backend, ChargenError, LogBackend};
+use std::sync::Arc;
+
+/// One character's trip through chargen.
+/// The session holds its backend - either owned outright (eg. in an Arc) or borrowed for as long
+/// as the session lives - and steps through each life stage's event in turn. Nothing here is
+/// global, so a process can run as many sessions as it likes, on whichever threads it likes.
+pub struct Session<'a, B: Backend + Clone + 'a> {
+    backend: B,
+    event: Option<Box<dyn Event + 'a>>,
+}
+
+impl<'a, B: Backend + Clone + 'a> Session<'a, B> {
+    pub fn new(backend: B) -> Self {
+        Self {
+            backend,
+            event: None,
+        }
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Whether an event is still waiting to present choices
+    pub fn in_event(&self) -> bool {
+        self.event.is_some()
+    }
+
+    /// Replaces whatever event is running, without touching the life stage
+    pub fn run_event(&mut self, event: impl Event + 'a) {
+        self.event = Some(Box::new(event));
+    }
+
+    /// Starts the event for the next life stage. Returns false once chargen is over.
+    pub fn advance_stage(&mut self) -> bool {
+        let stage = self.backend.get_character().life_stage;
+        match stage.next(self.backend.clone()) {
+            Some((stage, event)) => {
+                self.backend.get_character_mut().life_stage = stage;
+                self.event = Some(event);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The next choice from the current event, or None once that event is finished.
+    /// Answer it before asking for another.
+    pub fn next_choice(&mut self) -> Option<Choice> {
+        let choice = self.event.as_mut()?.next();
+        if choice.is_none() {
+            self.event = None;
+        }
+        choice
+    }
+}
+
+// iterating a session runs it through every remaining life stage
+impl<'a, B: Backend + Clone + 'a> Iterator for Session<'a, B> {
+    type Item = Choice;
+
+    fn next(&mut self) -> Option<Choice> {
+        loop {
+            if let Some(choice) = self.next_choice() {
+                return Some(choice);
+            }
+            if !self.advance_stage() {
+                return None;
+            }
+        }
+    }
+}
+
+/// A session that holds on to the choice it's waiting on, for front-ends that can only pass plain
+/// data back and forth - a server, a web page, another language. It runs straight through every
+/// life stage, and keeps its log so it can be saved like any other front-end's.
+pub struct RemoteSession {
+    session: Session<'static, Arc<LogBackend>>,
+    current_choice: Option<Choice>,
+}
+
+impl RemoteSession {
+    pub fn new(backend: LogBackend) -> Self {
+        let mut session = Session::new(Arc::new(backend));
+        let current_choice = session.next();
+        Self {
+            session,
+            current_choice,
+        }
+    }
+
+    /// Picks up a save in the same format as `character.sof`, from this release or an older one
+    pub fn load(save: &str) -> Result<Self, SaveError> {
+        Ok(Self::new(save::from_str(save)?))
+    }
+
+    pub fn save(&self) -> String {
+        save::to_string(self.backend())
+    }
+
+    pub fn backend(&self) -> &LogBackend {
+        self.session.backend()
+    }
+
+    /// What's being asked, or None once chargen is over
+    pub fn choice(&self) -> Option<ChoiceView> {
+        self.current_choice.as_ref().map(Choice::view)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current_choice.is_none()
+    }
+
+    /// Answers the current choice and moves on to the next one.
+    /// A reply that doesn't fit leaves the choice waiting for a better one.
+    pub fn reply(&mut self, reply: Reply) -> Result<(), ChargenError> {
+        let choice = self
+            .current_choice
+            .as_ref()
+            .ok_or_else(|| ChargenError::BadReply(String::from("chargen is already over")))?;
+        choice.reply(reply)?;
+        self.current_choice = None;
+        self.current_choice = self.session.next();
+        Ok(())
+    }
+}
+
+impl Default for RemoteSession {
+    fn default() -> Self {
+        Self::new(LogBackend::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::BaseBackend;
+
+    fn answer(choice: Choice) {
+        match choice {
+            Choice::Selection(s) => s.chosen.set(0),
+            Choice::String(t) => t.chosen.set(String::from("example")),
+            Choice::PickRoll(p) => p.chosen.set(None),
+            Choice::Question(q) => q.chosen.set(true),
+            Choice::Error(e) => panic!("{e}"),
+        }
+    }
+
+    #[test]
+    fn test_concurrent_sessions() {
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let mut session = Session::new(Arc::new(BaseBackend::default()));
+                std::thread::spawn(move || {
+                    for choice in &mut session {
+                        answer(choice);
+                    }
+                    session.backend().get_character().careers.len()
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 1);
+        }
+    }
+
+    #[test]
+    fn test_borrowed_backend() {
+        let backend = BaseBackend::default();
+        for choice in Session::new(&backend) {
+            answer(choice);
+        }
+        assert!(backend.get_character().omen.is_some());
+    }
+
+    #[test]
+    fn test_remote_session() {
+        let mut remote = RemoteSession::default();
+        while let Some(choice) = remote.choice() {
+            let reply = match choice {
+                ChoiceView::Selection { .. } => Reply::Selection(0),
+                ChoiceView::String { .. } => Reply::String(String::from("example")),
+                ChoiceView::PickRoll { .. } => Reply::PickRoll(None),
+                ChoiceView::Question { .. } => Reply::Question(true),
+                ChoiceView::Error { error, .. } => panic!("{error}"),
+            };
+            assert!(remote.reply(Reply::Dismiss).is_err());
+            remote.reply(reply).unwrap();
+        }
+        assert!(remote.is_finished());
+        assert!(remote.reply(Reply::Dismiss).is_err());
+        assert!(
+            remote
+                .backend()
+                .log
+                .read()
+                .unwrap()
+                .contains("You were born in")
+        );
+
+        let reloaded = RemoteSession::load(&remote.save()).unwrap();
+        assert_eq!(reloaded.save(), remote.save());
+        assert!(reloaded.is_finished());
+    }
+}
@@ -0,0 +1,109 @@
+// Characters as links. A character's save is deflated and written in URL-safe base64, so the
+// whole character fits in a URL fragment, which browsers keep to themselves rather than sending to
+// the server. It's the same save a file holds, so a link made by one release opens in any later
+// one, migrated like any other old save.
+use crate::LogBackend;
+use crate::save::SaveError;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use std::fmt::{Display, Formatter};
+
+// says how the rest of the link is written, in case there's ever another way, eg. a seed and answers
+const PREFIX: &str = "c1.";
+// far past any real save, so a link can't have gigabytes inflated from it
+const MAX_SAVE: usize = 1 << 20;
+
+#[derive(Debug)]
+pub enum ShareError {
+    /// There's no character in the link, or it's written in a way this release doesn't know
+    NotALink,
+    Encoding(base64::DecodeError),
+    /// The link's been cut short or garbled
+    Compression,
+    Save(SaveError),
+}
+
+impl Display for ShareError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShareError::NotALink => write!(f, "there's no character in the link"),
+            ShareError::Encoding(e) => write!(f, "the link's been garbled: {e}"),
+            ShareError::Compression => write!(f, "the link's been cut short or garbled"),
+            ShareError::Save(e) => write!(f, "the character in the link won't load: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ShareError {}
+
+/// The character as a URL fragment, without the #
+pub fn encode(backend: &LogBackend) -> String {
+    let save = crate::save::to_string(backend);
+    let deflated = miniz_oxide::deflate::compress_to_vec(save.as_bytes(), 9);
+    format!("{PREFIX}{}", URL_SAFE_NO_PAD.encode(deflated))
+}
+
+/// Reads the character from a link, or just its fragment
+pub fn decode(link: &str) -> Result<LogBackend, ShareError> {
+    let fragment = link.rsplit_once('#').map_or(link, |(_, fragment)| fragment);
+    let encoded = fragment.strip_prefix(PREFIX).ok_or(ShareError::NotALink)?;
+    let deflated = URL_SAFE_NO_PAD
+        .decode(encoded)
+        .map_err(ShareError::Encoding)?;
+    let save = miniz_oxide::inflate::decompress_to_vec_with_limit(&deflated, MAX_SAVE)
+        .map_err(|_| ShareError::Compression)?;
+    let save = String::from_utf8(save).map_err(|_| ShareError::Compression)?;
+    crate::save::from_str(&save).map_err(ShareError::Save)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn fixture() -> LogBackend {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/saves/v3.sof");
+        crate::save::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let backend = fixture();
+        let fragment = encode(&backend);
+        assert!(fragment.starts_with("c1."));
+        assert!(fragment.len() < crate::save::to_string(&backend).len());
+
+        let save = crate::save::to_string(&backend);
+        for link in [
+            fragment.clone(),
+            format!("#{fragment}"),
+            format!("https://example.com/chargen/?x=1#{fragment}"),
+        ] {
+            assert_eq!(crate::save::to_string(&decode(&link).unwrap()), save);
+        }
+    }
+
+    #[test]
+    fn test_bad_links() {
+        let fragment = encode(&fixture());
+        assert!(matches!(
+            decode("https://example.com/"),
+            Err(ShareError::NotALink)
+        ));
+        assert!(matches!(decode("#c9.AAAA"), Err(ShareError::NotALink)));
+        assert!(matches!(
+            decode("#c1.not base64"),
+            Err(ShareError::Encoding(_))
+        ));
+        // cut between whole groups of base64, so it's the deflated save that's short
+        let cut = PREFIX.len() + (fragment.len() - PREFIX.len()) / 8 * 4;
+        assert!(matches!(
+            decode(&fragment[..cut]),
+            Err(ShareError::Compression)
+        ));
+
+        let not_a_save = miniz_oxide::deflate::compress_to_vec(b"nonsense", 9);
+        let link = format!("#c1.{}", URL_SAFE_NO_PAD.encode(not_a_save));
+        assert!(matches!(decode(&link), Err(ShareError::Save(_))));
+    }
+}
@@ -0,0 +1,431 @@
+// Printable character sheets, for players who want them on paper or pasted into a campaign wiki.
+// A character is laid out once as sections of fields, tables and lists, and each format only
+// decides how to write those down, so the three never disagree about what's on a sheet.
+use crate::character::Stat;
+use crate::{CORE_STATS, Character};
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// How a sheet is written out
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SheetFormat {
+    Markdown,
+    Text,
+    /// A whole page, styles included, that needs nothing else to print
+    Html,
+}
+
+impl FromStr for SheetFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "markdown" | "md" => Ok(Self::Markdown),
+            "text" | "txt" => Ok(Self::Text),
+            "html" => Ok(Self::Html),
+            _ => Err(format!("no sheet format called \"{s}\"")),
+        }
+    }
+}
+
+impl SheetFormat {
+    /// The usual file extension, without the dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SheetFormat::Markdown => "md",
+            SheetFormat::Text => "txt",
+            SheetFormat::Html => "html",
+        }
+    }
+}
+
+const MISSING: &str = "-";
+const NON_SKILL_STATS: [Stat; 4] = [Stat::Magic, Stat::Luck, Stat::Stamina, Stat::Speed];
+
+enum Body {
+    Fields(Vec<(String, String)>),
+    Table {
+        header: Vec<&'static str>,
+        rows: Vec<Vec<String>>,
+    },
+    List(Vec<String>),
+}
+
+struct Section {
+    heading: &'static str,
+    body: Body,
+}
+
+fn or_missing(thing: Option<impl ToString>) -> String {
+    thing.map_or_else(|| MISSING.to_owned(), |thing| thing.to_string())
+}
+
+fn sections(character: &Character, log: &str) -> Vec<Section> {
+    let stat = |stat: Stat| or_missing(character.stats[stat]);
+
+    let skills = CORE_STATS
+        .iter()
+        .map(|&core| {
+            let subskills = core
+                .subskills()
+                .into_iter()
+                .map(|subskill| format!("{subskill} {}", stat(subskill)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            vec![core.to_string(), stat(core), subskills]
+        })
+        .collect();
+
+    let location = character.birth_location.as_ref();
+    let background = vec![
+        (
+            "Birthplace".to_owned(),
+            or_missing(location.map(|l| format!("{} ({:?})", l.name, l.demographic))),
+        ),
+        ("Culture".to_owned(), or_missing(character.culture)),
+        ("Faith".to_owned(), or_missing(character.faith)),
+        ("Omen".to_owned(), or_missing(character.omen)),
+        ("Affiliation".to_owned(), or_missing(character.affiliation)),
+        ("Rank".to_owned(), or_missing(character.rank)),
+    ];
+
+    let perks = CORE_STATS
+        .iter()
+        .map(|&core| {
+            let slots = character.perks.slots(core);
+            let taken: Vec<_> = slots
+                .iter()
+                .map(ToString::to_string)
+                .filter(|slot| !slot.is_empty())
+                .collect();
+            let free = format!("{} of {} free", slots.len() - taken.len(), slots.len());
+            let value = if taken.is_empty() {
+                free
+            } else {
+                format!("{}; {free}", taken.join(", "))
+            };
+            (core.to_string(), value)
+        })
+        .collect();
+
+    vec![
+        Section {
+            heading: "Skills",
+            body: Body::Table {
+                header: vec!["Skill", "Score", "Subskills"],
+                rows: skills,
+            },
+        },
+        Section {
+            heading: "Attributes",
+            body: Body::Fields(
+                NON_SKILL_STATS
+                    .iter()
+                    .map(|&s| (s.to_string(), stat(s)))
+                    .collect(),
+            ),
+        },
+        Section {
+            heading: "Background",
+            body: Body::Fields(background),
+        },
+        Section {
+            heading: "Careers",
+            body: Body::List(
+                character
+                    .careers
+                    .iter()
+                    .map(|career| format!("{} ({})", career.name(), career.class()))
+                    .collect(),
+            ),
+        },
+        Section {
+            heading: "Perk Slots",
+            body: Body::Fields(perks),
+        },
+        Section {
+            heading: "Traits",
+            body: Body::List(character.traits.clone()),
+        },
+        Section {
+            heading: "Life",
+            body: Body::List(
+                log.lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(String::from)
+                    .collect(),
+            ),
+        },
+    ]
+}
+
+fn title(character: &Character) -> &str {
+    if character.name.trim().is_empty() {
+        "Unnamed Character"
+    } else {
+        character.name.trim()
+    }
+}
+
+/// Writes out a character's sheet, with `log` (as kept by a `LogBackend`) as the story of their
+/// life so far
+pub fn render(character: &Character, log: &str, format: SheetFormat) -> String {
+    let title = title(character);
+    let sections = sections(character, log);
+    match format {
+        SheetFormat::Markdown => markdown(title, &sections),
+        SheetFormat::Text => text(title, &sections),
+        SheetFormat::Html => html(title, &sections),
+    }
+}
+
+fn escape_markdown(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if "\\`*_[]<>|#".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn markdown(title: &str, sections: &[Section]) -> String {
+    let mut out = format!("# {}\n", escape_markdown(title));
+    for section in sections {
+        write!(out, "\n## {}\n\n", section.heading).unwrap();
+        match &section.body {
+            Body::Fields(fields) => {
+                for (label, value) in fields {
+                    writeln!(out, "- **{label}:** {}", escape_markdown(value)).unwrap();
+                }
+            }
+            Body::Table { header, rows } => {
+                writeln!(out, "| {} |", header.join(" | ")).unwrap();
+                writeln!(out, "|{}", " --- |".repeat(header.len())).unwrap();
+                for row in rows {
+                    let row: Vec<_> = row.iter().map(|cell| escape_markdown(cell)).collect();
+                    writeln!(out, "| {} |", row.join(" | ")).unwrap();
+                }
+            }
+            Body::List(items) if items.is_empty() => out.push_str("*None*\n"),
+            Body::List(items) => {
+                for item in items {
+                    writeln!(out, "- {}", escape_markdown(item)).unwrap();
+                }
+            }
+        }
+    }
+    out
+}
+
+fn underline(out: &mut String, heading: &str, with: char) {
+    writeln!(out, "{heading}").unwrap();
+    let width = heading.chars().count();
+    writeln!(out, "{}", with.to_string().repeat(width)).unwrap();
+}
+
+fn text(title: &str, sections: &[Section]) -> String {
+    let mut out = String::new();
+    underline(&mut out, title, '=');
+    for section in sections {
+        out.push('\n');
+        underline(&mut out, section.heading, '-');
+        match &section.body {
+            Body::Fields(fields) => {
+                let width = fields.iter().map(|(l, _)| l.chars().count()).max();
+                for (label, value) in fields {
+                    let label = format!("{label}:");
+                    writeln!(out, "{label:<w$}{value}", w = width.unwrap_or(0) + 2).unwrap();
+                }
+            }
+            Body::Table { header, rows } => {
+                let widths: Vec<_> = (0..header.len())
+                    .map(|i| {
+                        rows.iter()
+                            .map(|row| row[i].chars().count())
+                            .chain([header[i].len()])
+                            .max()
+                            .unwrap()
+                    })
+                    .collect();
+                let header = header.iter().map(|h| h.to_string()).collect();
+                for row in [&header].into_iter().chain(rows) {
+                    let cells: Vec<_> = row
+                        .iter()
+                        .zip(&widths)
+                        .map(|(cell, &w)| format!("{cell:<w$}"))
+                        .collect();
+                    writeln!(out, "{}", cells.join("  ").trim_end()).unwrap();
+                }
+            }
+            Body::List(items) if items.is_empty() => out.push_str("None\n"),
+            Body::List(items) => {
+                for item in items {
+                    writeln!(out, "- {item}").unwrap();
+                }
+            }
+        }
+    }
+    out
+}
+
+pub(crate) fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+const STYLE: &str = "\
+body { font-family: Georgia, serif; max-width: 48em; margin: 2em auto; padding: 0 1em; color: #222; }
+h1 { border-bottom: 2px solid #222; }
+h2 { border-bottom: 1px solid #999; margin-top: 1.5em; }
+table { border-collapse: collapse; width: 100%; }
+th, td { border: 1px solid #999; padding: 0.25em 0.5em; text-align: left; }
+dl { display: grid; grid-template-columns: max-content auto; gap: 0.25em 1em; }
+dt { font-weight: bold; }
+dd { margin: 0; }
+@media print { body { margin: 0; max-width: none; } h2 { break-after: avoid; } }";
+
+fn html(title: &str, sections: &[Section]) -> String {
+    let title = escape_html(title);
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n<style>\n{STYLE}\n</style>\n</head>\n<body>\n<h1>{title}</h1>\n"
+    );
+    for section in sections {
+        writeln!(out, "<h2>{}</h2>", section.heading).unwrap();
+        match &section.body {
+            Body::Fields(fields) => {
+                out.push_str("<dl>\n");
+                for (label, value) in fields {
+                    writeln!(
+                        out,
+                        "<dt>{}</dt><dd>{}</dd>",
+                        escape_html(label),
+                        escape_html(value)
+                    )
+                    .unwrap();
+                }
+                out.push_str("</dl>\n");
+            }
+            Body::Table { header, rows } => {
+                out.push_str("<table>\n<tr>");
+                for cell in header {
+                    write!(out, "<th>{cell}</th>").unwrap();
+                }
+                out.push_str("</tr>\n");
+                for row in rows {
+                    out.push_str("<tr>");
+                    for cell in row {
+                        write!(out, "<td>{}</td>", escape_html(cell)).unwrap();
+                    }
+                    out.push_str("</tr>\n");
+                }
+                out.push_str("</table>\n");
+            }
+            Body::List(items) if items.is_empty() => out.push_str("<p>None</p>\n"),
+            Body::List(items) => {
+                out.push_str("<ul>\n");
+                for item in items {
+                    writeln!(out, "<li>{}</li>", escape_html(item)).unwrap();
+                }
+                out.push_str("</ul>\n");
+            }
+        }
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BirthOmen;
+    use crate::data::careers::{Affiliation, Career};
+    use crate::data::perks::Perk;
+
+    fn character() -> Character {
+        let mut character = Character {
+            name: "Ash <of> Duruza".to_owned(),
+            birth_location: crate::data::tables::bundled()
+                .location_by_id("duruza", crate::data::locations::Demographic::Urban),
+            omen: Some(BirthOmen::ShootingStar),
+            affiliation: Some(Affiliation::Watch),
+            rank: Some(3),
+            careers: vec![Career::from_id("peddler").unwrap()],
+            traits: vec!["Stubborn".to_owned()],
+            ..Default::default()
+        };
+        character.stats[Stat::Arms] = Some(40);
+        character.stats[Stat::Block] = Some(45);
+        character.stats[Stat::Luck] = Some(12);
+        character
+            .perks
+            .insert_perk(Stat::Arms, Perk::ArmourTraining, None)
+            .unwrap();
+        character
+    }
+
+    const LOG: &str = "\nYou were born in The Imperial Capital of Duruza.\n\nYou became a Peddler.";
+
+    #[test]
+    fn test_markdown() {
+        let sheet = render(&character(), LOG, SheetFormat::Markdown);
+        assert!(sheet.starts_with("# Ash \\<of\\> Duruza\n"));
+        assert!(sheet.contains("| Arms | 40 | Block 45, Swing -, Thrust - |"));
+        assert!(sheet.contains("- **Luck:** 12"));
+        assert!(sheet.contains("- **Birthplace:** The Imperial Capital of Duruza (Urban)"));
+        assert!(sheet.contains("- **Omen:** Shooting Star"));
+        assert!(sheet.contains("- **Arms:** Armour Training; 9 of 10 free"));
+        assert!(sheet.contains("- **Face:** 10 of 10 free"));
+        assert!(sheet.contains("- Peddler ("));
+        assert!(sheet.contains("- Stubborn"));
+        assert!(sheet.ends_with("- You became a Peddler.\n"));
+    }
+
+    #[test]
+    fn test_text() {
+        let sheet = render(&character(), LOG, SheetFormat::Text);
+        assert!(sheet.starts_with("Ash <of> Duruza\n===============\n"));
+        assert!(sheet.contains("Skill  Score  Subskills\n"));
+        assert!(sheet.contains("Arms   40     Block 45, Swing -, Thrust -\n"));
+        assert!(sheet.contains("Magic:   -\n"));
+        assert!(sheet.contains("Rank:        3\n"));
+        assert!(sheet.contains("Traits\n------\n- Stubborn\n"));
+    }
+
+    #[test]
+    fn test_html() {
+        let sheet = render(&character(), LOG, SheetFormat::Html);
+        assert!(sheet.starts_with("<!DOCTYPE html>"));
+        assert!(sheet.contains("<title>Ash &lt;of&gt; Duruza</title>"));
+        assert!(sheet.contains("<td>Arms</td><td>40</td>"));
+        assert!(sheet.contains("<dt>Faith</dt><dd>-</dd>"));
+        assert!(sheet.contains("<li>You were born in The Imperial Capital of Duruza.</li>"));
+        assert!(sheet.ends_with("</html>\n"));
+
+        // a character with nothing rolled still gets the whole sheet
+        let empty = render(&Character::default(), "", SheetFormat::Html);
+        assert!(empty.contains("<h1>Unnamed Character</h1>"));
+        assert!(empty.contains("<h2>Careers</h2>\n<p>None</p>"));
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!("Markdown".parse(), Ok(SheetFormat::Markdown));
+        assert_eq!("txt".parse(), Ok(SheetFormat::Text));
+        assert_eq!(SheetFormat::Html.extension(), "html");
+        assert!("pdf".parse::<SheetFormat>().is_err());
+    }
+}
@@ -0,0 +1,344 @@
+// Runs whole characters through chargen with nobody at the keyboard, answering each choice by a
+// policy, and counts up where they ended. The exact odds in `data::odds` only go as far as the
+// first career; everything after that depends on choices, which is what this is for.
+use crate::character::BIRTH_OMENS;
+use crate::dice;
+use crate::event::birth::OMEN_CHOICE;
+use crate::ipc::Choice;
+use crate::session::Session;
+use crate::{Backend, BirthOmen, Character, Stat};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+use std::sync::RwLock;
+
+// a character that's made this many choices is going round in circles, eg. refusing to convert
+// for a career and then picking it again
+const MAX_CHOICES: usize = 1000;
+
+/// How to pick from a list
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub enum Select {
+    #[default]
+    Random,
+    First,
+    Last,
+}
+
+/// How to answer yes or no questions, eg. whether to convert for a career
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub enum Answer {
+    #[default]
+    Random,
+    Always,
+    Never,
+}
+
+/// What to do when prophetic signs let you pick a roll
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub enum PickRoll {
+    /// Keep the roll
+    #[default]
+    Never,
+    Highest,
+    Lowest,
+}
+
+// lets the policy be given on the command line, eg. `--select first`
+macro_rules! from_str {
+    ($t: ident, $($name: literal => $variant: ident),+) => {
+        impl FromStr for $t {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s.to_lowercase().as_str() {
+                    $($name => Ok($t::$variant),)+
+                    _ => Err(format!(
+                        "expected one of {}, not \"{s}\"",
+                        [$($name),+].join(", ")
+                    )),
+                }
+            }
+        }
+    };
+}
+
+from_str!(Select, "random" => Random, "first" => First, "last" => Last);
+from_str!(Answer, "random" => Random, "always" => Always, "never" => Never);
+from_str!(PickRoll, "never" => Never, "highest" => Highest, "lowest" => Lowest);
+
+/// How a simulated player answers every choice
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct Policy {
+    pub select: Select,
+    pub answer: Answer,
+    pub pick_roll: PickRoll,
+    /// Always take this birth omen, rather than picking it like any other choice. It's parsed from
+    /// its name (eg. "Prophetic Signs"), so one that doesn't exist is turned away up front.
+    #[serde(default)]
+    pub omen: Option<BirthOmen>,
+}
+
+impl Policy {
+    fn answer(&self, choice: &Choice, rng: &mut impl Rng) -> Option<i8> {
+        match choice {
+            Choice::Selection(s) => {
+                // the omen's only ever forced when it's asked for, in the same order as BIRTH_OMENS
+                let forced = self
+                    .omen
+                    .filter(|_| {
+                        s.description == OMEN_CHOICE && s.options.len() == BIRTH_OMENS.len()
+                    })
+                    .and_then(|omen| BIRTH_OMENS.iter().position(|other| other.same_as(&omen)));
+                let last = s.options.len().saturating_sub(1);
+                s.chosen.set(forced.unwrap_or(match self.select {
+                    Select::Random => rng.random_range(0..=last),
+                    Select::First => 0,
+                    Select::Last => last,
+                }));
+                None
+            }
+            Choice::String(t) => {
+                t.chosen.set(String::from("simulated"));
+                None
+            }
+            Choice::PickRoll(p) => {
+                let range = p.roll.range();
+                let picked = match self.pick_roll {
+                    PickRoll::Never => None,
+                    PickRoll::Highest => Some(*range.end()),
+                    PickRoll::Lowest => Some(*range.start()),
+                };
+                p.chosen.set(picked);
+                picked
+            }
+            Choice::Question(q) => {
+                q.chosen.set(match self.answer {
+                    Answer::Random => rng.random(),
+                    Answer::Always => true,
+                    Answer::Never => false,
+                });
+                None
+            }
+            Choice::Error(_) => None,
+        }
+    }
+}
+
+/// How often each value came up, and their mean
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Distribution {
+    pub mean: f64,
+    pub counts: BTreeMap<i8, usize>,
+}
+
+impl Distribution {
+    fn add(&mut self, value: i8) {
+        *self.counts.entry(value).or_default() += 1;
+        let n: usize = self.counts.values().sum();
+        self.mean += (f64::from(value) - self.mean) / n as f64;
+    }
+}
+
+/// Everything that happened across a batch of simulated characters
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Report {
+    pub runs: usize,
+    /// Characters that didn't make it through chargen, by what stopped them
+    pub failures: BTreeMap<String, usize>,
+    pub stats: BTreeMap<String, Distribution>,
+    /// Characters whose magic reached 100
+    pub magic_deaths: usize,
+    pub birthplaces: BTreeMap<String, usize>,
+    pub cultures: BTreeMap<String, usize>,
+    pub faiths: BTreeMap<String, usize>,
+    pub affiliations: BTreeMap<String, usize>,
+    pub ranks: Distribution,
+    /// By career id
+    pub careers: BTreeMap<String, usize>,
+    pub omens: BTreeMap<String, usize>,
+    /// How many rolls were picked with prophetic signs
+    pub prophetic_picks: usize,
+}
+
+fn count(counts: &mut BTreeMap<String, usize>, key: impl ToString) {
+    *counts.entry(key.to_string()).or_default() += 1;
+}
+
+impl Report {
+    fn add(&mut self, character: &Character) {
+        for (stat, value) in character.stats.iter() {
+            if let Some(value) = value {
+                self.stats.entry(stat.to_string()).or_default().add(*value);
+            }
+        }
+        if character.stats[Stat::Magic].is_some_and(|magic| magic >= 100) {
+            self.magic_deaths += 1;
+        }
+        if let Some(location) = &character.birth_location {
+            count(&mut self.birthplaces, &location.id);
+        }
+        if let Some(culture) = character.culture {
+            count(&mut self.cultures, culture);
+        }
+        if let Some(faith) = character.faith {
+            count(&mut self.faiths, faith);
+        }
+        if let Some(affiliation) = character.affiliation {
+            count(&mut self.affiliations, affiliation);
+        }
+        if let Some(rank) = character.rank {
+            self.ranks.add(rank);
+        }
+        for career in &character.careers {
+            count(&mut self.careers, career.id());
+        }
+        // counted without however many charges of prophetic signs were left
+        match character.omen {
+            Some(BirthOmen::PropheticSigns(_)) => {
+                count(&mut self.omens, BirthOmen::PropheticSigns(0))
+            }
+            Some(omen) => count(&mut self.omens, omen),
+            None => {}
+        }
+    }
+}
+
+// a backend that keeps quiet, since nobody's reading the log
+#[derive(Default)]
+struct SimBackend {
+    character: RwLock<Character>,
+}
+
+impl Backend for SimBackend {
+    fn get_character_mut(&self) -> impl DerefMut<Target = Character> {
+        self.character.write().unwrap()
+    }
+
+    fn get_character(&self) -> impl Deref<Target = Character> {
+        self.character.read().unwrap()
+    }
+
+    fn log(&self, _text: String) {}
+}
+
+/// Runs a character all the way through chargen, answering every choice by `policy`.
+/// The dice are seeded from `rng` too, so a seeded `rng` always generates the same character.
+/// Returns the character, how many rolls were picked, and what stopped them if they didn't finish.
+pub fn generate(policy: &Policy, rng: &mut impl Rng) -> (Character, usize, Option<String>) {
+    let backend = SimBackend::default();
+    let mut picks = 0;
+    let mut failure = None;
+    dice::seeded(rng.random(), || {
+        for (i, choice) in Session::new(&backend).enumerate() {
+            if let Choice::Error(e) = &choice {
+                failure = Some(e.to_string());
+                break;
+            }
+            if i == MAX_CHOICES {
+                failure = Some(format!("stuck at \"{}\"", choice.description()));
+                break;
+            }
+            if policy.answer(&choice, rng).is_some() {
+                picks += 1;
+            }
+        }
+    });
+    (backend.character.into_inner().unwrap(), picks, failure)
+}
+
+/// Generates `runs` characters and reports on all of them
+pub fn simulate(runs: usize, policy: &Policy, rng: &mut impl Rng) -> Report {
+    let mut report = Report {
+        runs,
+        ..Default::default()
+    };
+    for _ in 0..runs {
+        let (character, picks, failure) = generate(policy, rng);
+        report.prophetic_picks += picks;
+        match failure {
+            Some(failure) => count(&mut report.failures, failure),
+            None => report.add(&character),
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulate() {
+        let report = simulate(200, &Policy::default(), &mut rand::rng());
+        assert_eq!(report.runs, 200);
+        assert!(report.failures.is_empty(), "{:?}", report.failures);
+        assert_eq!(report.birthplaces.values().sum::<usize>(), 200);
+        assert_eq!(report.omens.values().sum::<usize>(), 200);
+        assert!(report.careers.values().sum::<usize>() >= 200);
+        let arms = &report.stats["Arms"];
+        assert_eq!(arms.counts.values().sum::<usize>(), 200);
+        assert!((1.0..=100.0).contains(&arms.mean));
+        // it's data for other tools to read
+        assert!(ron::to_string(&report).is_ok());
+    }
+
+    #[test]
+    fn test_policy() {
+        let policy = Policy {
+            select: Select::First,
+            answer: Answer::Always,
+            pick_roll: PickRoll::Lowest,
+            omen: Some("prophetic".parse().unwrap()),
+        };
+        let report = simulate(50, &policy, &mut rand::rng());
+        assert!(report.failures.is_empty(), "{:?}", report.failures);
+        assert_eq!(report.omens["Prophetic Signs"], 50);
+        // the omen's first pick is always the affiliation reroll
+        assert!(report.prophetic_picks >= 50);
+    }
+
+    #[test]
+    fn test_seeded() {
+        use rand::SeedableRng;
+        let runs: Vec<_> = (0..2)
+            .map(|_| {
+                let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+                ron::to_string(&generate(&Policy::default(), &mut rng).0).unwrap()
+            })
+            .collect();
+        assert_eq!(runs[0], runs[1]);
+
+        // dice the caller seeded carry on where they were once the character's made
+        use crate::dice::DiceRoll;
+        let d100 = || crate::roll!(1 d 100).result();
+        dice::seed(9);
+        let around = [d100(), {
+            generate(&Policy::default(), &mut rand::rng());
+            d100()
+        }];
+        dice::seed(9);
+        assert_eq!(around, [d100(), d100()]);
+        dice::unseed();
+
+        assert_eq!("First".parse(), Ok(Select::First));
+        assert!("sometimes".parse::<Answer>().is_err());
+    }
+
+    #[test]
+    fn test_omen_names() {
+        assert_eq!("Shooting Star".parse(), Ok(BirthOmen::ShootingStar));
+        assert_eq!("portents".parse(), Ok(BirthOmen::PortentsOfDoom));
+        assert_eq!("Prophetic Signs".parse(), Ok(BirthOmen::PropheticSigns(2)));
+        assert!("Shootng".parse::<BirthOmen>().is_err());
+        // "p" could be prophetic signs, practically-minded or portents of doom
+        assert!(
+            "p".parse::<BirthOmen>()
+                .unwrap_err()
+                .contains("could be any of")
+        );
+        assert!("".parse::<BirthOmen>().is_err());
+    }
+}
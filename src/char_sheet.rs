@@ -9,7 +9,7 @@ use sof_chargen::CORE_STATS;
 use sof_chargen::Stat::{Luck, Magic, Stamina};
 use std::iter::once;
 
-pub fn stats(backend: &AppBackend) -> Row<Message> {
+pub fn stats(backend: &AppBackend) -> Row<'_, Message> {
     row(CORE_STATS.map(|stat| {
         row([
             column(
@@ -36,11 +36,11 @@ pub fn stats(backend: &AppBackend) -> Row<Message> {
     }))
 }
 
-fn top_row(backend: &AppBackend) -> Row<Message> {
+fn top_row(backend: &AppBackend, editable: bool) -> Row<'_, Message> {
     iced::widget::row![
         iced::widget::row![
             text_input("character name", &backend.get_character().name)
-                .on_input(Message::NameChanged)
+                .on_input_maybe(editable.then_some(Message::NameChanged))
         ]
         .width(Length::FillPortion(3)),
         present("Stamina", backend.get_stat(Stamina)).width(Length::FillPortion(2)),
@@ -51,13 +51,14 @@ fn top_row(backend: &AppBackend) -> Row<Message> {
     .spacing(8)
 }
 
-fn culture_row(backend: &AppBackend) -> Row<Message> {
+fn culture_row(backend: &AppBackend) -> Row<'_, Message> {
     let char = backend.get_character();
     iced::widget::row![
         present("Born", char.birth_location.as_ref().map(|l| &l.name))
             .width(Length::FillPortion(3)),
-        present("Culture", char.culture).width(Length::FillPortion(2)),
-        present("Faith", char.faith).width(Length::FillPortion(2)),
+        // until your omen is picked, you're shown as following your guardians
+        present("Culture", char.culture.or(char.guardians.culture)).width(Length::FillPortion(2)),
+        present("Faith", char.faith.or(char.guardians.faith)).width(Length::FillPortion(2)),
         present("Omen", char.omen).width(Length::FillPortion(2)),
     ]
     .width(Length::Fill)
@@ -79,14 +80,18 @@ fn debug_buttons() -> Row<'static, Message> {
     ]
 }
 
-pub fn char_sheet(backend: &AppBackend) -> Column<Message> {
-    iced::widget::column! {
-        top_row(backend),
+pub fn char_sheet(backend: &AppBackend, editable: bool) -> Column<'_, Message> {
+    let sheet = iced::widget::column! {
+        top_row(backend, editable),
         culture_row(backend),
         horizontal_rule(1),
         stats(backend),
         horizontal_rule(1),
-        debug_buttons().padding(5).spacing(5).wrap(),
+    };
+    if editable {
+        sheet.push(debug_buttons().padding(5).spacing(5).wrap())
+    } else {
+        sheet
     }
     .width(20 * 10 * 5)
 }
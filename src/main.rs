@@ -1,17 +1,23 @@
 mod backend;
 mod char_sheet;
+mod roster;
 mod save;
 mod sidebar;
+mod storage;
 mod util;
 
+use crate::backend::AppBackend;
+use crate::roster::{Entry, Id, LoadProblem, Roster};
+use crate::storage::PlatformStorage;
 use iced::font::Family;
-use iced::widget::Row;
-use iced::{Font, Settings, Theme};
-use sof_chargen::event::Event;
+use iced::widget::{Column, Row, button, column, pick_list, row, text};
+use iced::{Font, Settings, Task, Theme};
 use sof_chargen::event::{birth, scenarios};
 use sof_chargen::ipc::Choice;
+use sof_chargen::session::Session;
 use sof_chargen::{Backend, Character, event};
 use std::borrow::Cow;
+use std::sync::Arc;
 
 fn load_fonts() -> Vec<Cow<'static, [u8]>> {
     vec![
@@ -43,24 +49,74 @@ pub fn main() -> iced::Result {
         .run()
 }
 
-#[derive(Default)]
 struct App {
-    current_event: Option<Box<dyn Event>>,
+    session: Session<'static, Arc<AppBackend>>,
     current_choice: Option<Choice>,
+    roster: Roster<PlatformStorage>,
+    // the character being made, who's in the roster unless there was nowhere to keep them
+    id: Id,
+    // what the player should know about the last change to the roster, eg. that it went wrong
+    notice: Option<String>,
+    // whether the player's been asked if they're sure about deleting the character
+    confirming_delete: bool,
+    // a save file the player's loaded, until they say where it goes
+    #[cfg(target_arch = "wasm32")]
+    loaded: Option<LoadedSave>,
+    // the save that didn't load, until the player's seen it
+    load_problem: Option<LoadProblem>,
+    // off if a save that didn't load couldn't be kept aside, so it isn't saved over, or if there's
+    // nowhere to save at all
+    saving: bool,
+    // how many times the name's been changed since it was last saved, while the player's typing
+    typing: Option<u32>,
 
     trait_entry: String,
     dice_slider: i16,
 }
 
+#[cfg(target_arch = "wasm32")]
+struct LoadedSave {
+    file: String,
+    name: String,
+    // the save brought up to date, since it's been loaded to check it anyway
+    save: String,
+}
+
 #[derive(Debug, Clone)]
 enum Message {
     NameChanged(String),
+    // the name's gone unchanged for a moment since that many edits, so it can be saved
+    NameSettled(u32),
     Choose(usize),
     SubmitTrait(String),
     PickRoll(Option<i8>),
     SliderChanged(i16),
     QuestionAnswer(bool),
+    DismissError,
+    ExportBrokenSave,
+    DismissLoadProblem,
     ResetAll,
+    // roster
+    SwitchCharacter(Entry),
+    NewCharacter,
+    DuplicateCharacter,
+    DeleteCharacter,
+    ConfirmDelete(bool),
+    MakeEditable,
+    #[cfg(target_arch = "wasm32")]
+    ShareLink,
+    #[cfg(target_arch = "wasm32")]
+    DownloadSave,
+    #[cfg(target_arch = "wasm32")]
+    LoadSave,
+    #[cfg(target_arch = "wasm32")]
+    SaveFilePicked(Result<Option<(String, String)>, String>),
+    #[cfg(target_arch = "wasm32")]
+    LoadAsNew,
+    #[cfg(target_arch = "wasm32")]
+    LoadOverCurrent,
+    #[cfg(target_arch = "wasm32")]
+    CancelLoad,
     // events
     RollStats,
     PickStar,
@@ -77,24 +133,136 @@ impl Message {
             self,
             // these are the gui events corresponding to IPCs
             // ie thees ones should cause the event iter to advance now that we're done responding
-            Self::Choose(_) | Self::SubmitTrait(_) | Self::PickRoll(_) | Self::QuestionAnswer(_)
+            Self::Choose(_)
+                | Self::SubmitTrait(_)
+                | Self::PickRoll(_)
+                | Self::QuestionAnswer(_)
+                | Self::DismissError
+        )
+    }
+
+    // whether it changes the character, which a read-only one mustn't be
+    fn edits_character(&self) -> bool {
+        matches!(
+            self,
+            Self::NameChanged(_)
+                | Self::Choose(_)
+                | Self::SubmitTrait(_)
+                | Self::PickRoll(_)
+                | Self::QuestionAnswer(_)
+                | Self::ResetAll
+                | Self::RollStats
+                | Self::PickStar
+                | Self::RollLocation
+                | Self::RollCareers
+                | Self::DebugSlider
+                | Self::DebugScenario(_)
+                | Self::AdvanceLifeStage
         )
     }
 }
 
+impl Default for App {
+    fn default() -> Self {
+        let mut roster = Roster::open(storage::platform());
+        let mut notice = None;
+        if roster.is_new() {
+            if let Some(save) = save::legacy() {
+                if let Err(e) = roster.import(&save) {
+                    notice = Some(format!("Couldn't bring the old save into the roster: {e}"));
+                }
+            }
+        }
+        let mut id = roster
+            .current()
+            .or(roster.entries().first())
+            .map(|entry| entry.id);
+        if let Some(link) = save::take_shared_link() {
+            match Self::open_shared(&mut roster, &link) {
+                Ok(shared) => id = Some(shared),
+                Err(e) => notice = Some(format!("Couldn't open the shared character: {e}")),
+            }
+        }
+        let id = match id.map_or_else(|| roster.create(), Ok) {
+            Ok(id) => Some(id),
+            Err(e) => {
+                notice = Some(format!(
+                    "Couldn't save a new character, so they won't be kept: {e}"
+                ));
+                None
+            }
+        };
+        // with nowhere to keep them, the character's only made in memory, under an id that's never
+        // given out, and not saved
+        let mut app = Self {
+            session: Session::new(Arc::default()),
+            current_choice: None,
+            roster,
+            id: id.unwrap_or_default(),
+            notice: None,
+            confirming_delete: false,
+            #[cfg(target_arch = "wasm32")]
+            loaded: None,
+            load_problem: None,
+            saving: false,
+            typing: None,
+            trait_entry: String::new(),
+            dice_slider: 0,
+        };
+        if let Some(id) = id {
+            app.switch(id);
+        }
+        // switching clears the notice, so one from starting up is only given now
+        app.notice = app.notice.or(notice);
+        app
+    }
+}
+
 impl App {
-    fn update(&mut self, message: Message) {
+    fn backend(&self) -> &AppBackend {
+        self.session.backend()
+    }
+
+    fn read_only(&self) -> bool {
+        self.roster.current().is_some_and(|entry| entry.read_only)
+    }
+
+    fn update(&mut self, message: Message) -> Task<Message> {
+        if self.read_only() && message.edits_character() {
+            return Task::none();
+        }
         let should_advance = message.should_advance();
-        let backend = &*save::BACKEND;
+        let backend = self.session.backend().clone();
+        let typing = matches!(message, Message::NameChanged(_) | Message::NameSettled(_));
+        // a name that's still being typed is saved before anything else happens, eg. switching away
+        if !typing && self.typing.is_some() {
+            self.save_name();
+        }
+        let mut task = Task::none();
 
         match message {
-            Message::NameChanged(name) => backend.get_character_mut().name = name,
+            // saved once the player stops typing, rather than at every keystroke
+            Message::NameChanged(name) => {
+                backend.get_character_mut().name = name;
+                match self.typing {
+                    Some(edits) => self.typing = Some(edits + 1),
+                    None => {
+                        self.typing = Some(0);
+                        task = Self::settle_name(0);
+                    }
+                }
+            }
+            Message::NameSettled(edits) => match self.typing {
+                Some(now) if now == edits => self.save_name(),
+                Some(now) => task = Self::settle_name(now),
+                None => {}
+            },
             Message::Choose(i) => match &self.current_choice {
                 Some(Choice::Selection(s)) => s.chosen.set(i),
                 _ => panic!("attempted to choose when there is no choice!"),
             },
             Message::SubmitTrait(submission) => {
-                if self.current_event.is_some() {
+                if self.session.in_event() {
                     println!("TODO: trait submitted, do something {submission}");
                     // self.log_choice(&submission);
                 }
@@ -112,60 +280,359 @@ impl App {
                 Some(Choice::Question(q)) => q.chosen.set(a),
                 _ => panic!("attempted to answer a question when none were posed!"),
             },
+            // the event that failed has already finished, so advancing just clears the error
+            Message::DismissError => {}
+            Message::ExportBrokenSave => return self.export_broken_save(),
+            Message::DismissLoadProblem => self.load_problem = None,
             Message::ResetAll => {
+                self.roster.back_up_backend(self.id, &backend);
                 *backend.get_character_mut() = Character::default();
-                backend.log.borrow_mut().clear();
+                backend.log.write().unwrap().clear();
             }
-            Message::RollStats => {
-                self.current_event = Some(Box::new(birth::roll_core_stats(backend)));
+            Message::SwitchCharacter(entry) => self.switch(entry.id),
+            Message::NewCharacter => {
+                let id = self.roster.create();
+                self.switch_to_new(id);
             }
-            Message::PickStar => self.current_event = Some(Box::new(birth::pick_omens(backend))),
-            Message::RollLocation => birth::roll_location_of_birth(backend),
-            Message::RollCareers => {
-                self.current_event = Some(Box::new(birth::affiliation_rank_careers(backend)))
+            Message::DuplicateCharacter => {
+                let id = self.roster.duplicate(self.id);
+                self.switch_to_new(id);
             }
-            Message::SliderChanged(v) => self.dice_slider = v,
-            Message::DebugSlider => {
-                self.current_event = Some(Box::new(event::test_pick_dice(backend)))
+            Message::DeleteCharacter => self.confirming_delete = true,
+            Message::ConfirmDelete(sure) => {
+                self.confirming_delete = false;
+                if sure {
+                    self.delete();
+                }
             }
+            Message::MakeEditable => {
+                if let Err(e) = self.roster.set_read_only(self.id, false) {
+                    self.notice = Some(format!("Couldn't make the character editable: {e}"));
+                }
+            }
+            #[cfg(target_arch = "wasm32")]
+            Message::ShareLink => self.share_link(),
+            #[cfg(target_arch = "wasm32")]
+            Message::DownloadSave => self.download_save(),
+            #[cfg(target_arch = "wasm32")]
+            Message::LoadSave => return Task::perform(save::pick_file(), Message::SaveFilePicked),
+            #[cfg(target_arch = "wasm32")]
+            Message::SaveFilePicked(picked) => self.save_file_picked(picked),
+            #[cfg(target_arch = "wasm32")]
+            Message::LoadAsNew => {
+                if let Some(loaded) = self.loaded.take() {
+                    let id = self.roster.import(&loaded.save);
+                    self.switch_to_new(id);
+                }
+            }
+            #[cfg(target_arch = "wasm32")]
+            Message::LoadOverCurrent => {
+                if let Some(loaded) = self.loaded.take() {
+                    self.load_over_current(&loaded.save);
+                }
+            }
+            #[cfg(target_arch = "wasm32")]
+            Message::CancelLoad => self.loaded = None,
+            Message::RollStats => self.session.run_event(birth::roll_core_stats(backend)),
+            Message::PickStar => self.session.run_event(birth::pick_omens(backend)),
+            Message::RollLocation => {
+                if let Err(e) = birth::roll_location_of_birth(&backend) {
+                    self.current_choice = Some(Choice::Error(e));
+                }
+            }
+            Message::RollCareers => self
+                .session
+                .run_event(birth::affiliation_rank_careers(backend)),
+            Message::SliderChanged(v) => self.dice_slider = v,
+            Message::DebugSlider => self.session.run_event(event::test_pick_dice(backend)),
             Message::DebugScenario(i) => {
                 self.current_choice = None;
-                match i {
-                    1 => self.current_event = Some(Box::from(scenarios::kremish_accorder(backend))),
-                    2 => {
-                        self.current_event =
-                            Some(Box::from(scenarios::non_kremish_accorder(backend)))
+                let start = match i {
+                    1 => scenarios::kremish_accorder(),
+                    2 => scenarios::non_kremish_accorder(),
+                    _ => {
+                        println!("invalid debug scenario!");
+                        return Task::none();
                     }
-                    _ => println!("invalid debug scenario!"),
-                }
+                };
+                *backend.get_character_mut() = start;
+                self.session.run_event(scenarios::roll_career(backend));
             }
             Message::AdvanceLifeStage => {
                 self.current_choice = None;
-                self.current_event = backend.next_stage();
+                self.session.advance_stage();
+            }
+        }
+
+        if should_advance || (self.current_choice.is_none() && self.session.in_event()) {
+            self.current_choice = None;
+            self.current_choice = self.session.next_choice();
+        }
+
+        if !typing {
+            self.save();
+        }
+        task
+    }
+
+    // saved between events, since that's where a session can be picked up from
+    fn save(&mut self) {
+        if self.saving && !self.session.in_event() {
+            if let Err(e) = self.roster.save(self.id, self.session.backend()) {
+                self.notice = Some(format!("Couldn't save: {e}"));
+            }
+        }
+    }
+
+    // the name's saved with the rest of the character, but one that isn't being saved, eg. because
+    // their save wouldn't load, can still be renamed in the roster
+    fn save_name(&mut self) {
+        self.typing = None;
+        if self.saving {
+            self.save();
+        } else if self.roster.current().is_some() {
+            let name = self.backend().get_character().name.clone();
+            if let Err(e) = self.roster.rename(self.id, &name) {
+                self.notice = Some(format!("Couldn't rename the character: {e}"));
+            }
+        }
+    }
+
+    // checks back after a moment whether the name's still being typed
+    fn settle_name(edits: u32) -> Task<Message> {
+        Task::perform(save::settle(), move |()| Message::NameSettled(edits))
+    }
+
+    // adds the character in a shared link to the roster, read-only so nothing's changed by accident
+    fn open_shared(roster: &mut Roster<PlatformStorage>, link: &str) -> Result<Id, String> {
+        let backend = sof_chargen::share::decode(link).map_err(|e| e.to_string())?;
+        let id = roster.import(&sof_chargen::save::to_string(&backend))?;
+        roster.set_read_only(id, true)?;
+        Ok(id)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn share_link(&mut self) {
+        let copied =
+            save::share_link(self.backend()).and_then(|link| save::copy_to_clipboard(&link));
+        self.notice = Some(match copied {
+            Ok(()) => {
+                "Copied a link to the character. Whoever opens it gets their own copy.".to_owned()
+            }
+            Err(e) => format!("Couldn't make a link to the character: {e}"),
+        });
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn download_save(&mut self) {
+        let name = self
+            .roster
+            .current()
+            .map_or("character", |entry| &entry.name);
+        let save = sof_chargen::save::to_string(self.backend());
+        if let Err(e) = save::download(&format!("{name}.sof"), &save) {
+            self.notice = Some(format!("Couldn't download the save: {e}"));
+        }
+    }
+
+    // a file that won't load is turned away here, so nothing's ever replaced by one
+    #[cfg(target_arch = "wasm32")]
+    fn save_file_picked(&mut self, picked: Result<Option<(String, String)>, String>) {
+        match picked {
+            Ok(Some((file, text))) => match sof_chargen::save::from_str(&text) {
+                Ok(backend) => {
+                    self.notice = None;
+                    self.loaded = Some(LoadedSave {
+                        file,
+                        name: backend.get_character().name.clone(),
+                        save: sof_chargen::save::to_string(&backend),
+                    });
+                }
+                Err(e) => self.notice = Some(format!("{file} isn't a save that loads: {e}")),
+            },
+            Ok(None) => {}
+            Err(e) => self.notice = Some(format!("Couldn't read the file: {e}")),
+        }
+    }
+
+    // the current save's backed up first, so even this can be undone
+    #[cfg(target_arch = "wasm32")]
+    fn load_over_current(&mut self, save: &str) {
+        self.roster.back_up_backend(self.id, self.backend());
+        let replaced = sof_chargen::save::from_str(save)
+            .map_err(|e| e.to_string())
+            .and_then(|backend| self.roster.save(self.id, &backend));
+        match replaced {
+            Ok(()) => self.switch(self.id),
+            Err(e) => self.notice = Some(format!("Couldn't load the save: {e}")),
+        }
+    }
+
+    /// Stops making the current character and carries on with another from the roster
+    fn switch(&mut self, id: Id) {
+        match self.roster.switch(id) {
+            Ok((backend, load_problem)) => {
+                self.saving = load_problem
+                    .as_ref()
+                    .is_none_or(|problem| problem.kept.is_ok());
+                self.load_problem = load_problem;
+                self.session = Session::new(Arc::new(backend));
+                self.current_choice = None;
+                self.id = id;
+                self.notice = None;
             }
+            Err(e) => self.notice = Some(format!("Couldn't switch characters: {e}")),
         }
+    }
 
-        if should_advance || (self.current_choice.is_none() && self.current_event.is_some()) {
-            self.advance_event();
+    fn switch_to_new(&mut self, id: Result<Id, String>) {
+        match id {
+            Ok(id) => self.switch(id),
+            Err(e) => self.notice = Some(format!("Couldn't add a character: {e}")),
         }
+    }
 
-        if self.current_event.is_none() {
-            save::save_backend();
+    // there's always a character being made, so deleting the last one starts a new one
+    fn delete(&mut self) {
+        if let Err(e) = self.roster.delete(self.id) {
+            self.notice = Some(format!("Couldn't delete the character: {e}"));
+            return;
+        }
+        match self.roster.entries().first() {
+            Some(entry) => self.switch(entry.id),
+            None => {
+                let id = self.roster.create();
+                self.switch_to_new(id);
+            }
         }
     }
 
-    fn advance_event(&mut self) {
-        self.current_choice = None;
-        self.current_choice = self.current_event.as_mut().unwrap().next();
-        if self.current_choice.is_none() {
-            self.current_event = None;
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_broken_save(&self) -> Task<Message> {
+        match &self.load_problem {
+            Some(problem) => iced::clipboard::write(problem.raw.clone()),
+            None => Task::none(),
         }
     }
 
-    fn view(&self) -> Row<Message> {
-        iced::widget::row! {
-            char_sheet::char_sheet(&save::BACKEND),
-            self.sidebar(&save::BACKEND),
+    #[cfg(target_arch = "wasm32")]
+    fn export_broken_save(&mut self) -> Task<Message> {
+        if let Some(problem) = &self.load_problem {
+            if let Err(e) = save::download("broken-character.sof", &problem.raw) {
+                self.notice = Some(format!("Couldn't download the broken save: {e}"));
+            }
+        }
+        Task::none()
+    }
+
+    fn load_problem(problem: &LoadProblem) -> Column<'_, Message> {
+        let kept = match &problem.kept {
+            Ok(place) => format!("It's been kept as {place}, and chargen has started over."),
+            Err(e) => format!(
+                "It couldn't be kept aside ({e}), so nothing will be saved until chargen is \
+                 restarted. Export it before then."
+            ),
+        };
+        let export = if cfg!(target_arch = "wasm32") {
+            "Download the broken save"
+        } else {
+            "Copy the broken save"
+        };
+        column![
+            text("Your save couldn't be loaded."),
+            text(&problem.error).size(16),
+            text(kept),
+            row![
+                button(export).on_press(Message::ExportBrokenSave),
+                button("Dismiss").on_press(Message::DismissLoadProblem),
+            ]
+            .spacing(5),
+        ]
+        .spacing(5)
+        .padding(10)
+    }
+
+    fn roster(&self) -> Row<'_, Message> {
+        let current = self.roster.current().cloned();
+        let read_only = self.read_only();
+        // a character can't be left halfway through an event, as there's no saving it there
+        let bar = if self.session.in_event() {
+            row![text(current.map(|entry| entry.name).unwrap_or_default())]
+        } else if let Some(bar) = self.loaded_bar(read_only) {
+            bar
+        } else if self.confirming_delete {
+            let name = current.map(|entry| entry.name).unwrap_or_default();
+            row![
+                text(format!(
+                    "Delete {name}, and their backups? This can't be undone."
+                )),
+                button("Delete").on_press(Message::ConfirmDelete(true)),
+                button("Keep").on_press(Message::ConfirmDelete(false)),
+            ]
+        } else {
+            let mut bar = row![
+                pick_list(self.roster.entries(), current, Message::SwitchCharacter),
+                button("New").on_press(Message::NewCharacter),
+                button("Duplicate").on_press(Message::DuplicateCharacter),
+                button("Delete").on_press(Message::DeleteCharacter),
+            ];
+            #[cfg(target_arch = "wasm32")]
+            {
+                bar = bar
+                    .push(button("Share link").on_press(Message::ShareLink))
+                    .push(button("Download save").on_press(Message::DownloadSave))
+                    .push(button("Load save").on_press(Message::LoadSave));
+            }
+            if read_only {
+                bar = bar
+                    .push(text("Shared with you, so it's read-only."))
+                    .push(button("Edit").on_press(Message::MakeEditable));
+            }
+            bar
+        };
+        match &self.notice {
+            Some(e) => bar.push(text(e)),
+            None => bar,
+        }
+        .spacing(5)
+        .padding(5)
+    }
+
+    // asks where a loaded save goes. Only a character that can be saved over is offered as a place
+    #[cfg(target_arch = "wasm32")]
+    fn loaded_bar(&self, read_only: bool) -> Option<Row<'_, Message>> {
+        let loaded = self.loaded.as_ref()?;
+        let name = if loaded.name.is_empty() {
+            "an unnamed character"
+        } else {
+            &loaded.name
+        };
+        let current = self.roster.current().map_or("", |entry| &entry.name);
+        let mut bar = row![
+            text(format!("{} has {name} in it.", loaded.file)),
+            button("Add as a new character").on_press(Message::LoadAsNew),
+        ];
+        if self.saving && !read_only {
+            bar = bar.push(
+                button(text(format!("Replace {current}"))).on_press(Message::LoadOverCurrent),
+            );
+        }
+        Some(bar.push(button("Cancel").on_press(Message::CancelLoad)))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn loaded_bar(&self, _read_only: bool) -> Option<Row<'_, Message>> {
+        None
+    }
+
+    fn view(&self) -> Column<'_, Message> {
+        let sheet = row![
+            char_sheet::char_sheet(self.backend(), !self.read_only()),
+            self.sidebar(self.backend()),
+        ];
+        match &self.load_problem {
+            Some(problem) => column![self.roster(), Self::load_problem(problem), sheet],
+            None => column![self.roster(), sheet],
         }
     }
 
@@ -0,0 +1,464 @@
+// The characters the app keeps, each with its own save. A save that won't load is never
+// overwritten - it's kept aside before anything else is saved, and the player's told why it didn't
+// load. The last few saves of each character that did load are kept as backups too, in case one
+// that loads turns out to be one the player didn't want.
+//
+// The index of characters is kept beside their saves, but only so they can be listed without
+// reading every save. If it's lost or won't load, it's rebuilt from the saves.
+use crate::backend::AppBackend;
+use crate::storage::Storage;
+use serde::{Deserialize, Serialize};
+use sof_chargen::Backend;
+use std::fmt::{Display, Formatter};
+
+const BACKUPS: usize = 5;
+const INDEX: &str = "roster.ron";
+
+pub type Id = u32;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Slot {
+    Current,
+    // 1 is the most recent
+    Backup(usize),
+    // numbered so one broken save can't push out another
+    Quarantine(usize),
+}
+
+fn key(id: Id, slot: Slot) -> String {
+    match slot {
+        Slot::Current => format!("character-{id}.sof"),
+        Slot::Backup(n) => format!("character-{id}.backup-{n}.sof"),
+        Slot::Quarantine(n) => format!("character-{id}.broken-{n}.sof"),
+    }
+}
+
+// the character any key belongs to
+fn id_of(key: &str) -> Option<Id> {
+    key.strip_prefix("character-")?
+        .split('.')
+        .next()?
+        .parse()
+        .ok()
+}
+
+// what a character's called in the roster, which is their name once they have one
+fn name_of(id: Id, name: &str) -> String {
+    if name.is_empty() {
+        format!("Character {id}")
+    } else {
+        name.to_owned()
+    }
+}
+
+// the name in a save, if it loads
+fn name_in(save: Option<String>) -> String {
+    save.and_then(|save| sof_chargen::save::from_str(&save).ok())
+        .map(|backend| backend.get_character().name.clone())
+        .unwrap_or_default()
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub id: Id,
+    pub name: String,
+    /// Shown but not changed, eg. a character someone's shared, until the player says otherwise
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+impl Display for Entry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Index {
+    current: Option<Id>,
+    entries: Vec<Entry>,
+}
+
+/// Why a save didn't load, and what became of it
+#[derive(Clone, Debug)]
+pub struct LoadProblem {
+    pub error: String,
+    /// The save as it was, for exporting
+    pub raw: String,
+    /// Where it was kept aside, or why it couldn't be
+    pub kept: Result<String, String>,
+}
+
+#[derive(Debug)]
+pub struct Roster<S> {
+    storage: S,
+    index: Index,
+    // there was no index, so nothing's been kept here before
+    new: bool,
+}
+
+impl<S: Storage> Roster<S> {
+    pub fn open(storage: S) -> Self {
+        let index = storage.read(INDEX);
+        let mut roster = Self {
+            new: index.is_none(),
+            storage,
+            index: Index::default(),
+        };
+        match index.and_then(|index| ron::from_str(&index).ok()) {
+            Some(index) => roster.index = index,
+            None => roster.rebuild(),
+        }
+        roster
+    }
+
+    // finds every character with a save, in the order they were made
+    fn rebuild(&mut self) {
+        let mut ids: Vec<_> = self
+            .storage
+            .keys()
+            .iter()
+            .filter_map(|key| id_of(key).filter(|&id| *key == self::key(id, Slot::Current)))
+            .collect();
+        ids.sort();
+        self.index.entries = ids
+            .into_iter()
+            .map(|id| Entry {
+                id,
+                name: name_of(id, &name_in(self.storage.read(&key(id, Slot::Current)))),
+                read_only: false,
+            })
+            .collect();
+        self.index.current = None;
+    }
+
+    fn write_index(&self) -> Result<(), String> {
+        let index = ron::to_string(&self.index).expect("failed to serialize roster!");
+        self.storage.write(INDEX, &index)
+    }
+
+    fn entry_mut(&mut self, id: Id) -> Result<&mut Entry, String> {
+        self.index
+            .entries
+            .iter_mut()
+            .find(|entry| entry.id == id)
+            .ok_or_else(|| format!("there's no character {id}"))
+    }
+
+    /// Whether this is the first time the roster's been opened
+    pub fn is_new(&self) -> bool {
+        self.new
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.index.entries
+    }
+
+    /// The character that was last switched to
+    pub fn current(&self) -> Option<&Entry> {
+        let current = self.index.current?;
+        self.index.entries.iter().find(|entry| entry.id == current)
+    }
+
+    /// Adds a blank character
+    pub fn create(&mut self) -> Result<Id, String> {
+        self.import(&sof_chargen::save::to_string(&AppBackend::default()))
+    }
+
+    /// Adds a character from a save, which is kept as it is even if it won't load
+    pub fn import(&mut self, save: &str) -> Result<Id, String> {
+        // past every key, so nothing left behind by a character that's gone is taken as this one's
+        let id = self
+            .storage
+            .keys()
+            .iter()
+            .filter_map(|key| id_of(key))
+            .chain(self.index.entries.iter().map(|entry| entry.id))
+            .max()
+            .unwrap_or_default()
+            + 1;
+        self.storage.write(&key(id, Slot::Current), save)?;
+        self.index.entries.push(Entry {
+            id,
+            name: name_of(id, &name_in(Some(save.to_owned()))),
+            read_only: false,
+        });
+        self.write_index()?;
+        Ok(id)
+    }
+
+    /// Adds a copy of a character, as they were last saved
+    pub fn duplicate(&mut self, id: Id) -> Result<Id, String> {
+        let save = self
+            .storage
+            .read(&key(id, Slot::Current))
+            .ok_or_else(|| format!("there's no save for character {id}"))?;
+        self.import(&save)
+    }
+
+    /// Renames the character in their save as well as the roster, if their save loads
+    pub fn rename(&mut self, id: Id, name: &str) -> Result<(), String> {
+        let save = self.storage.read(&key(id, Slot::Current));
+        if let Some(backend) = save.and_then(|save| sof_chargen::save::from_str(&save).ok()) {
+            backend.get_character_mut().name = name.to_owned();
+            self.storage.write(
+                &key(id, Slot::Current),
+                &sof_chargen::save::to_string(&backend),
+            )?;
+        }
+        self.entry_mut(id)?.name = name_of(id, name);
+        self.write_index()
+    }
+
+    pub fn set_read_only(&mut self, id: Id, read_only: bool) -> Result<(), String> {
+        self.entry_mut(id)?.read_only = read_only;
+        self.write_index()
+    }
+
+    /// Deletes the character, and their backups and broken saves with them
+    pub fn delete(&mut self, id: Id) -> Result<(), String> {
+        for key in self.storage.keys() {
+            if id_of(&key) == Some(id) {
+                self.storage.remove(&key)?;
+            }
+        }
+        self.index.entries.retain(|entry| entry.id != id);
+        if self.index.current == Some(id) {
+            self.index.current = None;
+        }
+        self.write_index()
+    }
+
+    /// Makes the character the current one, and loads them
+    pub fn switch(&mut self, id: Id) -> Result<(AppBackend, Option<LoadProblem>), String> {
+        self.entry_mut(id)?;
+        self.index.current = Some(id);
+        self.write_index()?;
+        Ok(self.load(id))
+    }
+
+    /// The character as they were saved, or a new one if there's no save or it won't load
+    pub fn load(&self, id: Id) -> (AppBackend, Option<LoadProblem>) {
+        let Some(raw) = self.storage.read(&key(id, Slot::Current)) else {
+            return (AppBackend::default(), None);
+        };
+        match sof_chargen::save::from_str(&raw) {
+            Ok(backend) => {
+                self.back_up(id, &raw);
+                (backend, None)
+            }
+            Err(e) => {
+                let kept = self.quarantine(id, &raw);
+                let problem = LoadProblem {
+                    error: e.to_string(),
+                    raw,
+                    kept,
+                };
+                (AppBackend::default(), Some(problem))
+            }
+        }
+    }
+
+    /// Saves the character, and keeps their name in the roster up to date
+    pub fn save(&mut self, id: Id, backend: &AppBackend) -> Result<(), String> {
+        self.storage.write(
+            &key(id, Slot::Current),
+            &sof_chargen::save::to_string(backend),
+        )?;
+        let name = name_of(id, &backend.get_character().name);
+        let entry = self.entry_mut(id)?;
+        if entry.name != name {
+            entry.name = name;
+            self.write_index()?;
+        }
+        Ok(())
+    }
+
+    /// Keeps the character as they are as their most recent backup, pushing out the oldest
+    pub fn back_up_backend(&self, id: Id, backend: &AppBackend) {
+        self.back_up(id, &sof_chargen::save::to_string(backend));
+    }
+
+    fn back_up(&self, id: Id, save: &str) {
+        if self.storage.read(&key(id, Slot::Backup(1))).as_deref() == Some(save) {
+            return;
+        }
+        for n in (1..BACKUPS).rev() {
+            if let Some(older) = self.storage.read(&key(id, Slot::Backup(n))) {
+                let _ = self.storage.write(&key(id, Slot::Backup(n + 1)), &older);
+            }
+        }
+        // a backup that can't be written isn't worth stopping chargen over
+        let _ = self.storage.write(&key(id, Slot::Backup(1)), save);
+    }
+
+    fn quarantine(&self, id: Id, save: &str) -> Result<String, String> {
+        let mut n = 1;
+        let key = loop {
+            let key = key(id, Slot::Quarantine(n));
+            match self.storage.read(&key) {
+                // already kept aside, eg. by the last time the same broken save was loaded
+                Some(kept) if kept == save => break key,
+                Some(_) => n += 1,
+                None => {
+                    self.storage.write(&key, save)?;
+                    break key;
+                }
+            }
+        };
+        Ok(self.storage.describe(&key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    fn named(name: &str) -> AppBackend {
+        let backend = AppBackend::default();
+        backend.get_character_mut().name = name.to_owned();
+        backend
+    }
+
+    fn names<S: Storage>(roster: &Roster<S>) -> Vec<&str> {
+        roster
+            .entries()
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_roster() {
+        let mut roster = Roster::open(MemoryStorage::default());
+        assert!(roster.is_new());
+        assert!(roster.entries().is_empty());
+        assert!(roster.current().is_none());
+
+        let ash = roster.create().unwrap();
+        roster.save(ash, &named("Ash")).unwrap();
+        let copy = roster.duplicate(ash).unwrap();
+        let rowan = roster.create().unwrap();
+        assert_eq!(names(&roster), ["Ash", "Ash", "Character 3"]);
+
+        roster.set_read_only(ash, true).unwrap();
+        roster.rename(copy, "Birch").unwrap();
+        roster.rename(rowan, "Rowan").unwrap();
+        let (birch, problem) = roster.switch(copy).unwrap();
+        assert!(problem.is_none());
+        assert_eq!(birch.get_character().name, "Birch");
+        assert_eq!(roster.current().unwrap().name, "Birch");
+        assert!(roster.entries()[0].read_only);
+        assert!(!roster.current().unwrap().read_only);
+
+        // deleting takes the backup loading made with it, and the id isn't used again
+        roster.delete(copy).unwrap();
+        assert!(roster.current().is_none());
+        assert!(roster.switch(copy).is_err());
+        assert!(
+            roster
+                .storage
+                .keys()
+                .iter()
+                .all(|key| id_of(key) != Some(copy))
+        );
+        assert_eq!(names(&roster), ["Ash", "Rowan"]);
+        assert_eq!(roster.create().unwrap(), 4);
+
+        // the index is only a convenience, and what's lost with it is found again
+        roster.switch(ash).unwrap();
+        roster.storage.write(INDEX, "not an index").unwrap();
+        let roster = Roster::open(roster.storage);
+        assert!(!roster.is_new());
+        assert_eq!(names(&roster), ["Ash", "Rowan", "Character 4"]);
+        assert!(roster.current().is_none());
+    }
+
+    #[test]
+    fn test_quarantine() {
+        let mut roster = Roster::open(MemoryStorage::default());
+        let id = roster.import("not a save").unwrap();
+        assert_eq!(names(&roster), ["Character 1"]);
+
+        let (backend, problem) = roster.switch(id).unwrap();
+        let problem = problem.unwrap();
+        assert_eq!(problem.raw, "not a save");
+        assert_eq!(
+            problem.kept.unwrap(),
+            "\"character-1.broken-1.sof\" in memory"
+        );
+        assert!(backend.get_character().name.is_empty());
+
+        // saving over it now loses nothing, and the next broken save doesn't push it out
+        roster.save(id, &backend).unwrap();
+        let broken = |n| roster.storage.read(&key(id, Slot::Quarantine(n)));
+        assert_eq!(broken(1).unwrap(), "not a save");
+        roster
+            .storage
+            .write(&key(id, Slot::Current), "also not a save")
+            .unwrap();
+        assert!(roster.load(id).1.is_some());
+        let broken = |n| roster.storage.read(&key(id, Slot::Quarantine(n)));
+        assert_eq!(broken(1).unwrap(), "not a save");
+        assert_eq!(broken(2).unwrap(), "also not a save");
+
+        // a character whose save won't load can still be renamed in the roster
+        roster.rename(id, "Ash").unwrap();
+        assert_eq!(names(&roster), ["Ash"]);
+    }
+
+    #[test]
+    fn test_quarantine_once() {
+        let mut roster = Roster::open(MemoryStorage::default());
+        let id = roster.import("not a save").unwrap();
+        let kept: Vec<_> = (0..2)
+            .map(|_| roster.load(id).1.unwrap().kept.unwrap())
+            .collect();
+        assert_eq!(kept[0], kept[1]);
+        let quarantined: Vec<_> = roster
+            .storage
+            .keys()
+            .into_iter()
+            .filter(|key| key.contains(".broken-"))
+            .collect();
+        assert_eq!(quarantined, ["character-1.broken-1.sof"]);
+    }
+
+    #[test]
+    fn test_backups() {
+        let mut roster = Roster::open(MemoryStorage::default());
+        let id = roster.create().unwrap();
+        for i in 0..=BACKUPS {
+            roster.save(id, &named(&format!("Character {i}"))).unwrap();
+            let (loaded, problem) = roster.load(id);
+            assert!(problem.is_none());
+            assert_eq!(loaded.get_character().name, format!("Character {i}"));
+        }
+
+        // loading the same save again doesn't fill the backups with copies of it
+        roster.load(id);
+        let names: Vec<_> = (1..=BACKUPS + 1)
+            .map(|n| {
+                roster.storage.read(&key(id, Slot::Backup(n))).map(|save| {
+                    sof_chargen::save::from_str(&save)
+                        .unwrap()
+                        .get_character()
+                        .name
+                        .clone()
+                })
+            })
+            .collect();
+        assert_eq!(
+            names,
+            [
+                Some("Character 5".to_owned()),
+                Some("Character 4".to_owned()),
+                Some("Character 3".to_owned()),
+                Some("Character 2".to_owned()),
+                Some("Character 1".to_owned()),
+                None,
+            ]
+        );
+    }
+}
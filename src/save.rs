@@ -1,50 +1,138 @@
+// Moving saves in and out of the app. Before the roster there was just the one save, in
+// `character.sof` in the working directory natively (where the TUI still keeps it) and under "save"
+// in local storage on the web; it's brought into the roster the first time the app starts. The web
+// build also shares characters as links, which open as new characters in the roster, and downloads
+// and loads save files, which are the same as `character.sof` natively.
+#[cfg(target_arch = "wasm32")]
 use crate::backend::AppBackend;
-use std::sync::LazyLock;
+
+/// The save from before the roster, if there is one
+#[cfg(not(target_arch = "wasm32"))]
+pub fn legacy() -> Option<String> {
+    std::fs::read_to_string("character.sof").ok()
+}
 
 #[cfg(target_arch = "wasm32")]
-fn get_string_from_storage() -> Option<String> {
-    web_sys::window()?
-        .local_storage()
-        .ok()?
-        .map(|storage| storage.get_item("save").ok())??
+pub fn legacy() -> Option<String> {
+    let storage = web_sys::window()?.local_storage().ok()??;
+    storage.get_item("save").ok()?
 }
 
+/// The character shared in the page's link, if there is one. It's taken out of the address bar, so
+/// reloading the page doesn't bring them in again.
 #[cfg(target_arch = "wasm32")]
-fn save_string_to_storage(save: &str) {
-    web_sys::window()
-        .unwrap()
-        .local_storage()
-        .unwrap()
-        .map(|storage| storage.set_item("save", save).unwrap())
-        .unwrap()
+pub fn take_shared_link() -> Option<String> {
+    let window = web_sys::window()?;
+    let location = window.location();
+    let fragment = location.hash().ok().filter(|hash| hash.len() > 1)?;
+    let page = format!("{}{}", location.pathname().ok()?, location.search().ok()?);
+    if let Ok(history) = window.history() {
+        let _ = history.replace_state_with_url(&wasm_bindgen::JsValue::NULL, "", Some(&page));
+    }
+    Some(fragment)
 }
 
+// only the web build has a link to open
 #[cfg(not(target_arch = "wasm32"))]
-fn get_string_from_storage() -> Option<String> {
-    std::fs::read_to_string("character.sof").ok()
+pub fn take_shared_link() -> Option<String> {
+    None
 }
 
+/// A link to this page that opens the character
+#[cfg(target_arch = "wasm32")]
+pub fn share_link(backend: &AppBackend) -> Result<String, String> {
+    let window = web_sys::window().ok_or("there's no window")?;
+    let page = window.location().href().map_err(|e| format!("{e:?}"))?;
+    let page = page.split('#').next().unwrap_or_default();
+    Ok(format!("{page}#{}", sof_chargen::share::encode(backend)))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn copy_to_clipboard(text: &str) -> Result<(), String> {
+    let window = web_sys::window().ok_or("there's no window")?;
+    // the browser only says whether it was allowed once the player's moved on, so it isn't waited for
+    let _ = window.navigator().clipboard().write_text(text);
+    Ok(())
+}
+
+/// Offers `text` to the player as a file to download
+#[cfg(target_arch = "wasm32")]
+pub fn download(file_name: &str, text: &str) -> Result<(), String> {
+    use wasm_bindgen::JsCast;
+
+    let error = |e: wasm_bindgen::JsValue| format!("{e:?}");
+    let parts = js_sys::Array::of1(&text.into());
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type("text/plain");
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options).map_err(error)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(error)?;
+
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("there's no document")?;
+    let link: web_sys::HtmlAnchorElement = document
+        .create_element("a")
+        .map_err(error)?
+        .unchecked_into();
+    link.set_href(&url);
+    link.set_download(file_name);
+    link.click();
+    web_sys::Url::revoke_object_url(&url).map_err(error)
+}
+
+const SETTLE: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Waits long enough that the player's stopped typing, eg. before saving a name
 #[cfg(not(target_arch = "wasm32"))]
-fn save_string_to_storage(save: &str) {
-    std::fs::write("character.sof", save).unwrap();
+pub async fn settle() {
+    tokio::time::sleep(SETTLE).await;
 }
 
-static BACKEND_STR: LazyLock<String> = LazyLock::new(|| {
-    // the string is also a static because the backend holds the character, which holds careers,
-    // which have names and those names are static strings (even though they're serialised)
-    // really i should replace all the strings with enums and have a lookup table for the strings
-    // (since that'd be needed for supporting multiple languages anyway)
-    // but this isn't the worst thing in the world
-    get_string_from_storage().unwrap_or_default()
-});
+#[cfg(target_arch = "wasm32")]
+pub async fn settle() {
+    let timeout = js_sys::Promise::new(&mut |resolve, _| {
+        let millis = SETTLE.as_millis() as i32;
+        let set = web_sys::window().and_then(|window| {
+            window
+                .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, millis)
+                .ok()
+        });
+        // without a timer it's not waited for at all, rather than never
+        if set.is_none() {
+            let _ = resolve.call0(&wasm_bindgen::JsValue::NULL);
+        }
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(timeout).await;
+}
 
-// we don't use threads, so all types are vacuously Sync
-unsafe impl Sync for AppBackend {}
+/// Asks the player for a file, and reads it. Nothing comes back if they don't pick one.
+#[cfg(target_arch = "wasm32")]
+pub async fn pick_file() -> Result<Option<(String, String)>, String> {
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_futures::JsFuture;
 
-pub static BACKEND: LazyLock<AppBackend> =
-    LazyLock::new(|| ron::from_str(&BACKEND_STR).unwrap_or_default());
+    let error = |e: wasm_bindgen::JsValue| format!("{e:?}");
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("there's no document")?;
+    let input: web_sys::HtmlInputElement = document
+        .create_element("input")
+        .map_err(error)?
+        .unchecked_into();
+    input.set_type("file");
+    input.set_accept(".sof,text/plain");
+    let picked = js_sys::Promise::new(&mut |resolve, _| {
+        // either way the picker's closed, and the files say which
+        input.set_onchange(Some(&resolve));
+        let _ = input.add_event_listener_with_callback("cancel", &resolve);
+    });
+    input.click();
+    JsFuture::from(picked).await.map_err(error)?;
 
-pub fn save_backend() {
-    let save = ron::to_string(&*BACKEND).expect("failed to serialize backend!");
-    save_string_to_storage(&save);
+    let Some(file) = input.files().and_then(|files| files.get(0)) else {
+        return Ok(None);
+    };
+    let text = JsFuture::from(file.text()).await.map_err(error)?;
+    let text = text.as_string().ok_or("the file isn't text")?;
+    Ok(Some((file.name(), text)))
 }
@@ -55,12 +55,17 @@ impl App {
                 ]
                 .spacing(5)
             ],
+            Choice::Error(e) => column![
+                text(choice.description()),
+                text(e.to_string()),
+                button("Dismiss").on_press(Message::DismissError),
+            ],
         }
     }
 
-    pub(crate) fn sidebar(&self, backend: &AppBackend) -> Column<Message> {
+    pub(crate) fn sidebar(&self, backend: &AppBackend) -> Column<'_, Message> {
         column![
-            text(backend.log.borrow().clone()).size(16),
+            text(backend.log.read().unwrap().clone()).size(16),
             horizontal_rule(1),
         ]
         .push(if let Some(c) = &self.current_choice {
@@ -71,7 +76,8 @@ impl App {
                     "Current life stage: {:?}",
                     backend.get_character().life_stage
                 )),
-                button("Advance").on_press(Message::AdvanceLifeStage)
+                button("Advance")
+                    .on_press_maybe((!self.read_only()).then_some(Message::AdvanceLifeStage))
             ]
         })
     }
@@ -0,0 +1,216 @@
+// Somewhere to keep text by key. The roster only ever talks to a `Storage`, so the same logic runs
+// over localStorage on the web, files natively, and memory in tests.
+#[cfg(test)]
+use std::cell::RefCell;
+#[cfg(test)]
+use std::collections::BTreeMap;
+
+pub trait Storage {
+    fn read(&self, key: &str) -> Option<String>;
+    fn write(&self, key: &str, value: &str) -> Result<(), String>;
+    /// Does nothing if there's nothing there
+    fn remove(&self, key: &str) -> Result<(), String>;
+    fn keys(&self) -> Vec<String>;
+    /// Where a key's kept, for telling the player
+    fn describe(&self, key: &str) -> String;
+}
+
+/// Keeps nothing once it's dropped, for tests
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    values: RefCell<BTreeMap<String, String>>,
+}
+
+#[cfg(test)]
+impl Storage for MemoryStorage {
+    fn read(&self, key: &str) -> Option<String> {
+        self.values.borrow().get(key).cloned()
+    }
+
+    fn write(&self, key: &str, value: &str) -> Result<(), String> {
+        self.values
+            .borrow_mut()
+            .insert(key.to_owned(), value.to_owned());
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<(), String> {
+        self.values.borrow_mut().remove(key);
+        Ok(())
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.values.borrow().keys().cloned().collect()
+    }
+
+    fn describe(&self, key: &str) -> String {
+        format!("\"{key}\" in memory")
+    }
+}
+
+/// A file per key, in a directory of its own
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub struct FileStorage {
+    dir: std::path::PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileStorage {
+    // what's added to a key for the file it's written to before it's moved into place
+    const TEMP: &str = ".tmp";
+
+    pub fn new(dir: impl Into<std::path::PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The platform's data directory, eg. ~/.local/share/sof-chargen on Linux, or the working
+    /// directory if there isn't one
+    pub fn platform() -> Self {
+        Self::new(dirs::data_dir().map_or_else(Default::default, |dir| dir.join("sof-chargen")))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Storage for FileStorage {
+    fn read(&self, key: &str) -> Option<String> {
+        std::fs::read_to_string(self.dir.join(key)).ok()
+    }
+
+    // written beside the file and moved over it, so a crash halfway through can't leave half a save
+    fn write(&self, key: &str, value: &str) -> Result<(), String> {
+        std::fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+        let temp = self.dir.join(format!("{key}{}", Self::TEMP));
+        std::fs::write(&temp, value)
+            .and_then(|()| std::fs::rename(&temp, self.dir.join(key)))
+            .map_err(|e| {
+                let _ = std::fs::remove_file(&temp);
+                e.to_string()
+            })
+    }
+
+    fn remove(&self, key: &str) -> Result<(), String> {
+        match std::fs::remove_file(self.dir.join(key)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    fn keys(&self) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return vec![];
+        };
+        entries
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter(|key| !key.ends_with(Self::TEMP))
+            .collect()
+    }
+
+    fn describe(&self, key: &str) -> String {
+        self.dir.join(key).display().to_string()
+    }
+}
+
+/// The browser's localStorage, with every key under a prefix so others' keys are left alone
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug, Default)]
+pub struct LocalStorage;
+
+#[cfg(target_arch = "wasm32")]
+impl LocalStorage {
+    const PREFIX: &str = "sof-chargen/";
+
+    fn storage() -> Result<web_sys::Storage, String> {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok()?)
+            .ok_or_else(|| "there's no local storage".to_owned())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Storage for LocalStorage {
+    fn read(&self, key: &str) -> Option<String> {
+        Self::storage()
+            .ok()?
+            .get_item(&format!("{}{key}", Self::PREFIX))
+            .ok()?
+    }
+
+    fn write(&self, key: &str, value: &str) -> Result<(), String> {
+        Self::storage()?
+            .set_item(&format!("{}{key}", Self::PREFIX), value)
+            .map_err(|e| format!("{e:?}"))
+    }
+
+    fn remove(&self, key: &str) -> Result<(), String> {
+        Self::storage()?
+            .remove_item(&format!("{}{key}", Self::PREFIX))
+            .map_err(|e| format!("{e:?}"))
+    }
+
+    fn keys(&self) -> Vec<String> {
+        let Ok(storage) = Self::storage() else {
+            return vec![];
+        };
+        let length = storage.length().unwrap_or_default();
+        (0..length)
+            .filter_map(|i| storage.key(i).ok()?)
+            .filter_map(|key| Some(key.strip_prefix(Self::PREFIX)?.to_owned()))
+            .collect()
+    }
+
+    fn describe(&self, key: &str) -> String {
+        format!("\"{}{key}\" in local storage", Self::PREFIX)
+    }
+}
+
+/// Where this build keeps its characters
+#[cfg(not(target_arch = "wasm32"))]
+pub type PlatformStorage = FileStorage;
+
+#[cfg(target_arch = "wasm32")]
+pub type PlatformStorage = LocalStorage;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn platform() -> PlatformStorage {
+    FileStorage::platform()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn platform() -> PlatformStorage {
+    LocalStorage
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_storage() {
+        let dir = std::env::temp_dir().join(format!("sof-storage-{}", std::process::id()));
+        let storage = FileStorage::new(&dir);
+        assert_eq!(storage.keys(), Vec::<String>::new());
+        assert_eq!(storage.read("a"), None);
+
+        // the directory's made when it's first needed
+        storage.write("a", "one").unwrap();
+        storage.write("b", "two").unwrap();
+        assert_eq!(storage.read("a").as_deref(), Some("one"));
+        let mut keys = storage.keys();
+        keys.sort();
+        assert_eq!(keys, ["a", "b"]);
+
+        // writing over a key replaces it whole, and leaves nothing else behind
+        storage.write("a", "three").unwrap();
+        assert_eq!(storage.read("a").as_deref(), Some("three"));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+        std::fs::write(dir.join("b.tmp"), "left by a crash").unwrap();
+        assert_eq!(storage.keys().len(), 2);
+
+        storage.remove("a").unwrap();
+        storage.remove("a").unwrap();
+        assert_eq!(storage.read("a"), None);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}